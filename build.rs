@@ -1,8 +1,15 @@
 use cfg_aliases::cfg_aliases;
 
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(elf)");
+    println!("cargo:rustc-check-cfg=cfg(coff)");
+    println!("cargo:rustc-check-cfg=cfg(mach_o)");
+    println!("cargo:rustc-check-cfg=cfg(debug_mode)");
+    println!("cargo:rustc-check-cfg=cfg(support_priority)");
+
     cfg_aliases! {
 
+        // keep in sync with Format::cfg in static_init_macro
         elf: { any(
             target_os = "linux",
             target_os = "android",
@@ -0,0 +1,160 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Check the behavior of the platform layer of static_init on the current target.
+//!
+//! Run it with `cargo run --example platform_probe`. It prints one `key=value` line per
+//! capability or observation, followed by the order in which constructors, dynamic statics and
+//! destructors ran. The last line, printed after main returned, is `result=ok` or
+//! `result=fail`, and the process exit status is non zero on failure.
+//!
+//! Orders are only checked when the target supports priorities (`caps.ctor_priority=true`).
+
+use static_init::{caps, constructor, defer_at_exit, defer_at_thread_exit, destructor, dynamic};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+static EVENTS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+static MAIN_STARTED: AtomicBool = AtomicBool::new(false);
+
+static FAILED: AtomicBool = AtomicBool::new(false);
+
+fn record(event: &'static str) {
+    EVENTS.lock().unwrap().push(event)
+}
+
+fn take_events() -> Vec<&'static str> {
+    std::mem::take(&mut *EVENTS.lock().unwrap())
+}
+
+fn report(key: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(std::io::stdout(), "{}={}", key, value);
+}
+
+fn check(key: &str, ok: bool) {
+    if !ok {
+        FAILED.store(true, Ordering::Relaxed);
+    }
+    report(key, if ok { "ok" } else { "fail" });
+}
+
+fn check_order(key: &str, events: &[&str], expected: &[&str]) {
+    report(key, events.join(","));
+    if caps::HAS_CTOR_PRIORITY {
+        check(&format!("{}.check", key), events == expected);
+    }
+}
+
+#[constructor(20)]
+extern "C" fn ctor_20() {
+    record("ctor_20");
+}
+
+#[constructor(10)]
+extern "C" fn ctor_10() {
+    record("ctor_10");
+}
+
+struct Probe(&'static str);
+
+impl Probe {
+    fn new(name: &'static str) -> Self {
+        record(name);
+        Probe(name)
+    }
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        record(self.0)
+    }
+}
+
+#[dynamic(init = 15, drop = 15)]
+static DYN_15: Probe = Probe::new("dynamic_15");
+
+#[dynamic(init = 5, drop = 5)]
+static DYN_5: Probe = Probe::new("dynamic_5");
+
+// true if initialized before main
+#[dynamic(lazy)]
+static LESSER_LAZY: bool = !MAIN_STARTED.load(Ordering::Relaxed);
+
+#[dynamic(lazy, drop)]
+static DROPPED_LAZY: Probe = Probe("lazy_drop");
+
+#[destructor(7)]
+extern "C" fn dtor_7() {
+    record("dtor_7");
+}
+
+#[destructor(10)]
+extern "C" fn dtor_10() {
+    record("dtor_10");
+}
+
+#[destructor(last)]
+extern "C" fn final_report() {
+    let events = take_events();
+
+    check("at_exit", events.first() == Some(&"defer_at_exit"));
+    check("lazy_drop", events.contains(&"lazy_drop"));
+
+    let dtors: Vec<_> = events
+        .into_iter()
+        .filter(|e| !e.starts_with("defer") && *e != "lazy_drop")
+        .collect();
+    check_order(
+        "destructor_order",
+        &dtors,
+        &["dynamic_5", "dtor_7", "dtor_10", "dynamic_15"],
+    );
+
+    if FAILED.load(Ordering::Relaxed) {
+        report("result", "fail");
+        unsafe { libc::_exit(1) }
+    }
+    report("result", "ok");
+}
+
+fn main() {
+    MAIN_STARTED.store(true, Ordering::Relaxed);
+
+    report("caps.ctor", caps::HAS_CTOR);
+    report("caps.ctor_priority", caps::HAS_CTOR_PRIORITY);
+    report("caps.lesser_lazy", caps::HAS_LESSER_LAZY);
+    report("caps.atexit", caps::HAS_ATEXIT);
+    report("caps.thread_exit", caps::HAS_THREAD_EXIT);
+    report("caps.debug_mode", caps::DEBUG_MODE);
+
+    check_order(
+        "constructor_order",
+        &take_events(),
+        &["ctor_20", "dynamic_15", "ctor_10", "dynamic_5"],
+    );
+
+    check("dynamic_init", unsafe { DYN_15.0 == "dynamic_15" && DYN_5.0 == "dynamic_5" });
+
+    report("lesser_lazy.before_main", *LESSER_LAZY);
+    check("lesser_lazy", *LESSER_LAZY == caps::HAS_LESSER_LAZY);
+
+    std::thread::spawn(|| {
+        defer_at_thread_exit! {
+            record("thread_exit");
+        };
+    })
+    .join()
+    .unwrap();
+    check("thread_exit", take_events() == ["thread_exit"]);
+
+    assert_eq!(unsafe { DROPPED_LAZY.0 }, "lazy_drop");
+    defer_at_exit! {
+        record("defer_at_exit");
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Capabilities of this crate on the current target, as decided when it was built.
+//!
+//! Downstream code can branch on these constants instead of replicating the target detection
+//! of this crate.
+//!
+//! ```
+//! use static_init::caps;
+//!
+//! const _: () = assert!(!caps::HAS_CTOR_PRIORITY || caps::HAS_CTOR);
+//! ```
+
+/// Functions can be declared with the [macro@crate::constructor] and [macro@crate::destructor]
+/// attributes, and *dynamic statics* are supported.
+pub const HAS_CTOR: bool = cfg!(any(elf, coff, mach_o));
+
+/// Constructors, destructors and *dynamic statics* accept priorities other than `0`.
+pub const HAS_CTOR_PRIORITY: bool = cfg!(support_priority);
+
+/// *Lazy statics* declared with the `dynamic` attribute are *lesser lazy statics*: they are all
+/// initialized before main, and accesses after main do not check the initialization state.
+pub const HAS_LESSER_LAZY: bool = cfg!(all(
+    support_priority,
+    feature = "lazy",
+    feature = "macros",
+    not(feature = "test_no_global_lazy_hint")
+));
+
+/// Statics can be dropped at program exit with `#[dynamic(drop)]` (feature `atexit`).
+pub const HAS_ATEXIT: bool = cfg!(feature = "atexit");
+
+/// Thread local *lazy statics* can be dropped at thread exit (feature `thread_local_drop`).
+pub const HAS_THREAD_EXIT: bool = cfg!(feature = "thread_local_drop");
+
+/// Accesses to statics are checked for initialization order errors (debug mode).
+pub const DEBUG_MODE: bool = cfg!(debug_mode);
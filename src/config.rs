@@ -0,0 +1,93 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::{set_constructor_error_handler, set_fast_exit, ConstructorErrorHandler};
+use core::fmt;
+use core::sync::atomic::Ordering;
+
+/// The runtime settings of this crate, gathered in a single builder.
+///
+/// `Config` is a convenience to apply several settings at once: each setting corresponds to one
+/// of the functions [set_fast_exit], [disable_finalization](crate::disable_finalization) and
+/// [set_constructor_error_handler], and is read from there by the crate. Settings that are not
+/// given keep their current value.
+///
+/// The configuration should be applied before the statics and constructors it affects are run,
+/// for example from the first constructor:
+///
+/// ```
+/// use static_init::{constructor, Config};
+///
+/// #[constructor(first)]
+/// extern "C" fn configure() {
+///     Config::new().disable_finalization(true).apply();
+/// }
+/// # assert!(static_init::is_finalization_disabled());
+/// ```
+#[derive(Copy, Clone, Default)]
+pub struct Config {
+    fast_exit:                 Option<bool>,
+    disable_finalization:      Option<bool>,
+    constructor_error_handler: Option<ConstructorErrorHandler>,
+}
+
+impl Config {
+    /// A configuration that does not change any setting.
+    pub const fn new() -> Self {
+        Self {
+            fast_exit:                 None,
+            disable_finalization:      None,
+            constructor_error_handler: None,
+        }
+    }
+
+    /// See [set_fast_exit].
+    pub const fn fast_exit(mut self, v: bool) -> Self {
+        self.fast_exit = Some(v);
+        self
+    }
+
+    /// See [disable_finalization](crate::disable_finalization). With `false`, the registration
+    /// of drops of *lazy statics* is enabled again.
+    pub const fn disable_finalization(mut self, v: bool) -> Self {
+        self.disable_finalization = Some(v);
+        self
+    }
+
+    /// See [set_constructor_error_handler].
+    pub const fn constructor_error_handler(mut self, handler: ConstructorErrorHandler) -> Self {
+        self.constructor_error_handler = Some(handler);
+        self
+    }
+
+    /// Apply the settings.
+    pub fn apply(self) {
+        if let Some(v) = self.fast_exit {
+            set_fast_exit(v)
+        }
+        if let Some(v) = self.disable_finalization {
+            crate::FINALIZATION_DISABLED.store(v, Ordering::Release)
+        }
+        if let Some(h) = self.constructor_error_handler {
+            set_constructor_error_handler(h)
+        }
+    }
+}
+
+// Not derived: fn pointers taking a reference only implement Debug since Rust 1.70.
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("fast_exit", &self.fast_exit)
+            .field("disable_finalization", &self.disable_finalization)
+            .field(
+                "constructor_error_handler",
+                &self.constructor_error_handler.map(|h| h as *const ()),
+            )
+            .finish()
+    }
+}
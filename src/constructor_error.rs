@@ -0,0 +1,82 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use core::fmt;
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// The error returned by a constructor, passed to the constructor error handler.
+///
+/// See [set_constructor_error_handler].
+pub struct ConstructorError<'a> {
+    /// The name of the constructor function.
+    pub function: &'static str,
+    /// The error returned by the constructor.
+    pub error:    &'a dyn fmt::Debug,
+}
+
+impl fmt::Debug for ConstructorError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConstructorError")
+            .field("function", &self.function)
+            .field("error", self.error)
+            .finish()
+    }
+}
+
+impl fmt::Display for ConstructorError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "constructor `{}` failed: {:?}", self.function, self.error)
+    }
+}
+
+/// Signature of the constructor error handler.
+pub type ConstructorErrorHandler = fn(&ConstructorError<'_>);
+
+static HANDLER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+static ERROR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Set the function called when a constructor returns an error.
+///
+/// The handler only applies to constructors that run after this call, so it should be set by a
+/// constructor with a higher priority than the constructors that may fail. If the handler
+/// returns, the program initialization continues.
+///
+/// The default handler writes the error to stderr and aborts the program. When the standard
+/// library is not available (none of the features `lazy` and `thread_local_drop` is enabled) it
+/// panics, which aborts the program as the panic can not unwind out of the constructor.
+pub fn set_constructor_error_handler(handler: ConstructorErrorHandler) {
+    HANDLER.store(handler as *mut (), Ordering::Release)
+}
+
+/// Return the number of constructors that returned an error.
+pub fn constructor_error_count() -> usize {
+    ERROR_COUNT.load(Ordering::Acquire)
+}
+
+fn default_handler(e: &ConstructorError<'_>) {
+    #[cfg(any(feature = "lazy", feature = "thread_local_drop"))]
+    {
+        std::eprintln!("{}", e);
+        std::process::abort()
+    }
+    #[cfg(not(any(feature = "lazy", feature = "thread_local_drop")))]
+    core::panic!("{}", e)
+}
+
+#[doc(hidden)]
+#[cold]
+pub fn __constructor_error(function: &'static str, error: &dyn fmt::Debug) {
+    ERROR_COUNT.fetch_add(1, Ordering::AcqRel);
+    let p = HANDLER.load(Ordering::Acquire);
+    let handler: ConstructorErrorHandler = if p.is_null() {
+        default_handler
+    } else {
+        unsafe { core::mem::transmute::<*mut (), ConstructorErrorHandler>(p) }
+    };
+    handler(&ConstructorError { function, error })
+}
@@ -0,0 +1,126 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Executable checks of the initialization and drop order of *lazy statics*.
+//!
+//! In debug mode, *lazy statics* record the order in which their initialization ends. The
+//! functions of this module use this record and the drop mode of the statics to check an
+//! ordering relation the coder relies on, and panic if it does not hold. When not in debug mode
+//! these functions do nothing.
+//!
+//! ```ignore
+//! use static_init::{dynamic, debug};
+//!
+//! #[dynamic(lazy)]
+//! static A: Vec<i32> = vec![1];
+//!
+//! #[dynamic(lazy)]
+//! static B: usize = A.len();
+//!
+//! debug::assert_initialized_before(&A, &B);
+//! ```
+
+use crate::{ConstLazy, DropMode, Lazy, StaticInfo};
+
+/// *Lazy statics* whose order of initialization is recorded in debug mode.
+pub trait LazyOrder {
+    #[doc(hidden)]
+    fn __init_rank(&self) -> Option<usize>;
+    #[doc(hidden)]
+    fn __info(&self) -> Option<&StaticInfo>;
+}
+
+impl<T, F> LazyOrder for Lazy<T, F> {
+    fn __init_rank(&self) -> Option<usize> {
+        Lazy::init_rank(self)
+    }
+    fn __info(&self) -> Option<&StaticInfo> {
+        Lazy::info(self)
+    }
+}
+
+impl<T, F> LazyOrder for ConstLazy<T, F> {
+    fn __init_rank(&self) -> Option<usize> {
+        ConstLazy::init_rank(self)
+    }
+    fn __info(&self) -> Option<&StaticInfo> {
+        ConstLazy::info(self)
+    }
+}
+
+/// Check that the initialization of `a` ended before the one of `b`.
+///
+/// Panics if `a` is not initialized or if `b` was initialized first.
+pub fn assert_initialized_before(a: &dyn LazyOrder, b: &dyn LazyOrder) {
+    if !cfg!(debug_mode) {
+        return;
+    }
+    let ra = match a.__init_rank() {
+        Some(r) => r,
+        None => core::panic!(
+            "{:#?} is expected to be initialized before {:#?} but it is not initialized.",
+            a.__info(),
+            b.__info()
+        ),
+    };
+    if let Some(rb) = b.__init_rank() {
+        if rb < ra {
+            core::panic!(
+                "{:#?} is expected to be initialized before {:#?} but it was initialized after.",
+                a.__info(),
+                b.__info()
+            )
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn __assert_not_initialized(other: &dyn LazyOrder, name: &str, other_name: &str) {
+    if other.__init_rank().is_some() {
+        core::panic!(
+            "`{}` is declared to be initialized before `{}` but `{}` is already initialized: \
+             {:#?}",
+            name,
+            other_name,
+            other_name,
+            other.__info()
+        )
+    }
+}
+
+/// Check that `a` will be dropped after `b`, so that `a` can be used during the drop of `b`.
+///
+/// The relation holds if `a` is never dropped, if both statics have a drop priority and the
+/// priority of `a` is higher, or if both are dropped at exit and `a` was initialized first.
+/// Panics otherwise.
+pub fn assert_will_drop_after(a: &dyn LazyOrder, b: &dyn LazyOrder) {
+    if !cfg!(debug_mode) {
+        return;
+    }
+    let (ia, ib) = match (a.__info(), b.__info()) {
+        (Some(ia), Some(ib)) => (ia, ib),
+        _ => return,
+    };
+    let holds = match (&ia.drop_mode, &ib.drop_mode) {
+        (DropMode::None, _) => true,
+        (DropMode::Dynamic(pa), DropMode::Dynamic(pb)) => pa > pb,
+        (DropMode::AtExit, DropMode::AtExit) => match (a.__init_rank(), b.__init_rank()) {
+            (Some(ra), Some(rb)) => ra < rb,
+            (_, None) => true,
+            (None, Some(_)) => false,
+        },
+        _ => false,
+    };
+    if !holds {
+        core::panic!(
+            "{:#?} is expected to be dropped after {:#?} but their drops are not sequenced this \
+             way. Tip: use drop priorities (attribute syntax: `#[dynamic(lazy,drop=<prio>)]`)",
+            ia,
+            ib
+        )
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Count of the statics whose drop is pending, checked at program exit.
+//!
+//! With the feature `debug_drop_count`, in debug mode, the count is incremented when the drop of
+//! a *dynamic static* or of a global *lazy static* is scheduled and decremented when the static
+//! is dropped. A static dropped twice causes a panic, and a destructor run last checks that the
+//! count is back to zero.
+
+use super::StaticInfo;
+use core::sync::atomic::{AtomicIsize, Ordering};
+
+static PENDING_DROPS: AtomicIsize = AtomicIsize::new(0);
+
+#[inline]
+pub(crate) fn drop_registered() {
+    if cfg!(feature = "debug_drop_count") {
+        PENDING_DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[inline]
+pub(crate) fn drop_unregistered() {
+    if cfg!(feature = "debug_drop_count") {
+        PENDING_DROPS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[inline]
+pub(crate) fn assert_not_dropped(info: Option<&StaticInfo>, dropped: bool) {
+    if cfg!(feature = "debug_drop_count") && dropped {
+        if let Some(info) = info {
+            core::panic!("{:#?} is dropped twice.", info);
+        } else {
+            core::panic!("A static is dropped twice.");
+        }
+    }
+}
+
+#[cfg(all(feature = "debug_drop_count", support_priority))]
+#[static_init_macro::destructor(last)]
+extern "C" fn check_pending_drops() {
+    let pending = PENDING_DROPS.load(Ordering::Relaxed);
+    if pending != 0 && !crate::is_fast_exit() {
+        core::panic!(
+            "{} statics whose drop was scheduled were not dropped at program exit.",
+            pending
+        );
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+type Handler = Box<dyn FnOnce() + Send>;
+
+struct Registry<C> {
+    lock:     AtomicBool,
+    handlers: UnsafeCell<C>,
+}
+
+unsafe impl<C: Send> Sync for Registry<C> {}
+
+#[cfg(feature = "alloc")]
+static REGISTRY: Registry<Vec<Handler>> = Registry::new(Vec::new());
+
+impl<C> Registry<C> {
+    const fn new(handlers: C) -> Self {
+        Self {
+            lock:     AtomicBool::new(false),
+            handlers: UnsafeCell::new(handlers),
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut C) -> R) -> R {
+        while self
+            .lock
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        let r = f(unsafe { &mut *self.handlers.get() });
+        self.lock.store(false, Ordering::Release);
+        r
+    }
+}
+
+/// Register `f` to be run by [run_exit_handlers].
+///
+/// This is intended for targets that have an allocator but no way to register functions
+/// to be called at program exit.
+#[cfg(feature = "alloc")]
+pub fn push_exit_handler(f: impl FnOnce() + Send + 'static) {
+    REGISTRY.with(|v| v.push(Box::new(f)))
+}
+
+/// Run the handlers registered with [push_exit_handler], the last registered first.
+///
+/// Handlers registered while the handlers are run are also run. Once this function returns,
+/// the registry is empty.
+#[cfg(feature = "alloc")]
+pub fn run_exit_handlers() {
+    while let Some(f) = REGISTRY.with(|v| v.pop()) {
+        f()
+    }
+}
+
+#[cfg(feature = "atexit")]
+mod at_exit {
+    use super::{Box, Handler, Registry, Vec};
+    use core::fmt;
+
+    /// Error returned by [at_exit] when the handlers registered with it have already been run.
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub struct RegistrationClosed(());
+
+    impl fmt::Display for RegistrationClosed {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("the exit handlers have already been run")
+        }
+    }
+
+    #[cfg(any(feature = "lazy", feature = "thread_local_drop"))]
+    impl std::error::Error for RegistrationClosed {}
+
+    struct State {
+        runner_registered: bool,
+        closed:            bool,
+        handlers:          Vec<(u16, Handler)>,
+    }
+
+    static AT_EXIT: Registry<State> = Registry::new(State {
+        runner_registered: false,
+        closed:            false,
+        handlers:          Vec::new(),
+    });
+
+    /// Register `f` to be run at program exit.
+    ///
+    /// The priority only orders the handlers registered with this function: handlers with
+    /// priority 0 are run first, and handlers with the same priority are run in the reverse
+    /// order of their registration. Handlers registered while the handlers are run are also run.
+    ///
+    /// The handlers are not interleaved with [macro@crate::destructor] functions and the drops
+    /// of `#[dynamic(drop=<prio>)]` statics, whatever their priorities: all the handlers are run
+    /// by a single function registered with `atexit` by the first call. So they are run after
+    /// the functions registered with `atexit` after that first call, such as the drops of
+    /// `#[dynamic(lazy,drop)]` statics, and before any destructor.
+    ///
+    /// Returns an error once the handlers have been run, in which case `f` is not run.
+    ///
+    /// ```
+    /// use static_init::at_exit;
+    ///
+    /// at_exit(0, || println!("bye")).unwrap();
+    /// ```
+    pub fn at_exit(priority: u16, f: impl FnOnce() + Send + 'static) -> Result<(), RegistrationClosed> {
+        AT_EXIT.with(|state| {
+            if state.closed {
+                return Err(RegistrationClosed(()));
+            }
+            if !state.runner_registered {
+                crate::__atexit(run_at_exit);
+                state.runner_registered = true;
+            }
+            state.handlers.push((priority, Box::new(f)));
+            Ok(())
+        })
+    }
+
+    /// Objects dropped at program exit, in the order of the priority of the registry.
+    ///
+    /// This is a thin wrapper over [at_exit]: each registered object is boxed in a closure that
+    /// drops it, so objects of different registries are ordered by the priorities of their
+    /// registries, and objects of the same registry are dropped in the reverse order of their
+    /// registration.
+    ///
+    /// ```
+    /// use static_init::DropRegistry;
+    ///
+    /// static LATE: DropRegistry = DropRegistry::new(100);
+    ///
+    /// LATE.register(String::from("dropped at exit")).unwrap();
+    /// ```
+    #[derive(Debug)]
+    pub struct DropRegistry {
+        priority: u16,
+    }
+
+    impl DropRegistry {
+        /// A registry whose objects are dropped with the [at_exit] priority `priority`.
+        pub const fn new(priority: u16) -> Self {
+            Self { priority }
+        }
+
+        /// The [at_exit] priority of the drops of this registry.
+        pub const fn priority(&self) -> u16 {
+            self.priority
+        }
+
+        /// Register `v` to be dropped at program exit.
+        ///
+        /// Returns an error once the [at_exit] handlers have been run, in which case `v` is
+        /// dropped immediately.
+        pub fn register<T: Send + 'static>(&self, v: T) -> Result<(), RegistrationClosed> {
+            at_exit(self.priority, move || drop(v))
+        }
+    }
+
+    /// A long lived object that is notified at program exit.
+    ///
+    /// ```
+    /// use static_init::{register_exit_hook, ExitHook};
+    ///
+    /// struct Logger;
+    ///
+    /// impl ExitHook for Logger {
+    ///     fn on_exit(&self) {
+    ///         println!("flushed");
+    ///     }
+    /// }
+    ///
+    /// static LOGGER: Logger = Logger;
+    ///
+    /// register_exit_hook(&LOGGER, 10).unwrap();
+    /// ```
+    pub trait ExitHook: Sync {
+        /// Called once at program exit for each registration of this hook.
+        fn on_exit(&self);
+    }
+
+    /// Register `hook` to be notified at program exit with the [at_exit] priority `priority`.
+    ///
+    /// This is a thin wrapper over [at_exit], which boxes a closure calling the hook.
+    ///
+    /// Returns an error once the [at_exit] handlers have been run, in which case the hook is
+    /// not called.
+    pub fn register_exit_hook(hook: &'static dyn ExitHook, priority: u16) -> Result<(), RegistrationClosed> {
+        at_exit(priority, move || hook.on_exit())
+    }
+
+    extern "C" fn run_at_exit() {
+        loop {
+            let next = AT_EXIT.with(|state| {
+                // lowest priority, last registered
+                let i = state
+                    .handlers
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .min_by_key(|(_, (p, _))| *p)
+                    .map(|(i, _)| i);
+                match i {
+                    Some(i) => Some(state.handlers.remove(i).1),
+                    None => {
+                        state.closed = true;
+                        None
+                    }
+                }
+            });
+            match next {
+                Some(f) => f(),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "atexit")]
+pub use at_exit::{at_exit, register_exit_hook, DropRegistry, ExitHook, RegistrationClosed};
@@ -0,0 +1,174 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::StaticStorage;
+use core::hash::Hash;
+use parking_lot::{const_mutex, Mutex, Once};
+use std::boxed::Box;
+use std::collections::HashSet;
+
+/// Types of the values that can be interned.
+///
+/// The interner stores a copy of the values it is given. This trait is implemented for types
+/// that implement `Clone`, for `str` and for slices of `Clone` values.
+pub trait Internable: Eq + Hash + 'static {
+    /// Return a copy of `self` that is never dropped.
+    fn leak_copy(&self) -> &'static Self;
+}
+
+impl<T: Clone + Eq + Hash + 'static> Internable for T {
+    fn leak_copy(&self) -> &'static Self {
+        Box::leak(Box::new(self.clone()))
+    }
+}
+
+impl Internable for str {
+    fn leak_copy(&self) -> &'static Self {
+        Box::leak(Box::from(self))
+    }
+}
+
+impl<T: Clone + Eq + Hash + 'static> Internable for [T] {
+    fn leak_copy(&self) -> &'static Self {
+        Box::leak(self.to_vec().into_boxed_slice())
+    }
+}
+
+/// A set of values that live until the program exits.
+///
+/// Interning a value returns a `&'static` reference to the single copy of the value stored
+/// in the interner, so interned values can be compared by address. The interner can be used
+/// as a static:
+///
+/// ```
+/// use static_init::Interner;
+///
+/// static KEYWORDS: Interner<str> = Interner::new();
+///
+/// let a = KEYWORDS.intern("fn");
+/// let b = KEYWORDS.intern(&String::from("fn"));
+/// assert!(core::ptr::eq(a, b));
+/// ```
+///
+/// Any [Internable] type can be interned, for example `Interner<u32>` or `Interner<[u8]>`.
+///
+/// Interned values are never dropped: they may still be referenced by the drop of other
+/// statics at program exit, or by threads that still run while the program exits.
+pub struct Interner<T: ?Sized + 'static> {
+    set: Mutex<Option<HashSet<&'static T>>>,
+}
+
+impl<T: ?Sized + Internable> Interner<T> {
+    /// Create an empty interner.
+    pub const fn new() -> Self {
+        Self {
+            set: const_mutex(None),
+        }
+    }
+
+    /// Return the interned copy of `v`, adding a copy of it to the interner if needed.
+    pub fn intern(&self, v: &T) -> &'static T {
+        let mut l = self.set.lock();
+        let set = l.get_or_insert_with(HashSet::new);
+        if let Some(v) = set.get(v) {
+            return v;
+        }
+        let v = v.leak_copy();
+        set.insert(v);
+        v
+    }
+
+    /// Return the interned copy of `v` if any.
+    pub fn get(&self, v: &T) -> Option<&'static T> {
+        self.set.lock().as_ref().and_then(|s| s.get(v).copied())
+    }
+
+    /// The number of interned values.
+    pub fn len(&self) -> usize {
+        self.set.lock().as_ref().map_or(0, HashSet::len)
+    }
+
+    /// Return true if no value is interned.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: ?Sized + Internable> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static STRINGS: Interner<str> = Interner::new();
+
+/// Intern `s` in the global string interner, see [intern!](crate::intern!).
+pub fn intern_str(s: &str) -> &'static str {
+    STRINGS.intern(s)
+}
+
+/// The interned copy of a string literal, cached by an [intern!](crate::intern!) invocation.
+#[doc(hidden)]
+pub struct __InternedLiteral {
+    once:  Once,
+    value: StaticStorage<&'static str>,
+}
+
+unsafe impl Sync for __InternedLiteral {}
+
+impl __InternedLiteral {
+    pub const fn new() -> Self {
+        Self {
+            once:  Once::new(),
+            value: StaticStorage::uninit(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, s: &'static str) -> &'static str {
+        self.once
+            .call_once(|| unsafe { self.value.write(intern_str(s)) });
+        unsafe { *self.value.as_ptr() }
+    }
+}
+
+impl Default for __InternedLiteral {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Intern a string in the global string interner.
+///
+/// When the argument is a string literal, the interned string is cached by the invocation,
+/// so the global interner is only locked the first time the invocation is executed.
+///
+/// ```
+/// use static_init::intern;
+///
+/// let name = String::from("main");
+/// assert!(core::ptr::eq(intern!("main"), intern!(&name)));
+/// ```
+///
+/// Literals that are not strings are rejected:
+///
+/// ```compile_fail
+/// use static_init::intern;
+///
+/// intern!(3);
+/// ```
+#[macro_export]
+macro_rules! intern {
+    ($s:literal) => {{
+        const __STATIC_INIT_LITERAL: &str = $s;
+        static __STATIC_INIT_INTERNED: $crate::__InternedLiteral = $crate::__InternedLiteral::new();
+        __STATIC_INIT_INTERNED.get(__STATIC_INIT_LITERAL)
+    }};
+    ($s:expr) => {
+        $crate::intern_str($s)
+    };
+}
@@ -0,0 +1,114 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::StaticStorage;
+use core::fmt;
+use parking_lot::Once;
+
+struct Slot<T> {
+    value:  StaticStorage<T>,
+    initer: Once,
+}
+
+impl<T> Slot<T> {
+    // Only used as the operand of the array repeat expression.
+    #[allow(clippy::declare_interior_mutable_const)]
+    const UNINIT: Self = Self {
+        value:  StaticStorage::uninit(),
+        initer: Once::new(),
+    };
+}
+
+/// A fixed number of lazily initialized slots.
+///
+/// Each slot is initialized independently, on first call to [LazySlab::get_or_init] with its
+/// index. This is intended for frameworks that hand out one slot per registered component
+/// without heap allocation:
+///
+/// ```
+/// use static_init::LazySlab;
+///
+/// static PLUGINS: LazySlab<String, 4> = LazySlab::new();
+///
+/// assert_eq!(PLUGINS.get_or_init(1, || "audio".to_string()), "audio");
+/// assert_eq!(PLUGINS.get(1).map(String::as_str), Some("audio"));
+/// assert!(PLUGINS.get(0).is_none());
+/// ```
+pub struct LazySlab<T, const N: usize> {
+    slots: [Slot<T>; N],
+}
+
+unsafe impl<T: Send + Sync, const N: usize> Sync for LazySlab<T, N> {}
+
+impl<T, const N: usize> LazySlab<T, N> {
+    /// Create a slab whose slots are all uninitialized.
+    pub const fn new() -> Self {
+        Self {
+            slots: [Slot::UNINIT; N],
+        }
+    }
+
+    /// The number of slots.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Return true if the slab has no slot.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Return a reference to the value of slot `index`, initializing it with `f` if needed.
+    ///
+    /// Threads that access a slot while it is initialized wait for the end of its
+    /// initialization.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds or if a previous initialization of the slot panicked.
+    pub fn get_or_init(&self, index: usize, f: impl FnOnce() -> T) -> &T {
+        let slot = &self.slots[index];
+        slot.initer.call_once(|| unsafe { slot.value.write(f()) });
+        unsafe { &*slot.value.as_ptr() }
+    }
+
+    /// Return a reference to the value of slot `index` if it is initialized.
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let slot = self.slots.get(index)?;
+        if slot.initer.state().done() {
+            Some(unsafe { &*slot.value.as_ptr() })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, const N: usize> Default for LazySlab<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for LazySlab<T, N> {
+    fn drop(&mut self) {
+        for slot in &self.slots {
+            if slot.initer.state().done() {
+                unsafe { slot.value.drop_in_place() }
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for LazySlab<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries((0..N).map(|i| self.get(i))).finish()
+    }
+}
@@ -47,6 +47,10 @@
 //! blocks. The reason is that it is possible at program destruction to access already dropped
 //! lazy statics.
 //!
+//! On plateforms that support priorities, a drop priority can be given to *lesser lazy statics*
+//! with `#[dynamic(lazy,drop=<prio>)]`: they are then dropped by a program destructor so that
+//! their drop is ordered relative to *destructors* and *dynamic statics* drops.
+//!
 //! # Dynamic statics: statics initialized at program startup
 //!
 //! On plateforms that support it (unixes, mac, windows), this crate provides *dynamic statics*: statics that are
@@ -171,7 +175,12 @@
 //! #[dynamic(lazy)]
 //! static mut X: Vec<i32> = vec![1,2,3];
 //! ```
-//! These variables can also be droped on thread exit.
+//! These variables can also be droped on thread exit. The drop happens at the exit of any thread,
+//! including threads that were not created by the rust standard library (for example threads created
+//! by `pthread_create` or `CreateThread` in foreign code). A thread local
+//! first initialized while the thread locals of its thread are dropped is not dropped: its value
+//! is leaked, with a message on stderr, or the static is considered dropped with
+//! `#[dynamic(lazy,drop,on_late_register="error")]`.
 //! ```ignore
 //! #[thread_local]
 //! #[dynamic(lazy,drop)]
@@ -184,6 +193,53 @@
 //! droped may cause *undefined behavior*. For this reason any access to a thread local lazy static
 //! that is dropped will require an unsafe block, even if the static is const.
 //!
+//! Code that may run while thread locals are dropped, as the drop of an other thread local, can
+//! use `ThreadLocalConstLazy::try_with` (or `ThreadLocalLazy::try_with`) which returns an
+//! `AccessError` instead of accessing a value that has already been dropped.
+//!
+//! ```ignore
+//! let v = ThreadLocalConstLazy::try_with(unsafe{&X}, |x| x.len());
+//! ```
+//!
+//!
+//! # Fast exit
+//!
+//! If the program decides at run time that dropping statics at exit is unsafe, calling
+//! `static_init::set_fast_exit(true)` makes the drops registered by the `dynamic` attribute do nothing.
+//!
+//! # Overriding lazy statics in tests
+//!
+//! With the feature `test_override`, `Lazy::scoped_override` (and `ConstLazy::scoped_override`)
+//! replaces the value of a *lazy static* until the returned guard is dropped, so that tests can
+//! inject a fake value in a global static.
+//!
+//! ```ignore
+//! let _guard = unsafe { Lazy::scoped_override(&CONFIG, test_config()) };
+//! ```
+//!
+//! # Exit handlers without OS support
+//!
+//! With the feature `alloc`, boxed closures can be registered with `push_exit_handler` and run
+//! explicitly with `run_exit_handlers`, on targets that have an allocator but no exit hooks.
+//!
+//! With the feature `atexit`, `at_exit` registers boxed closures with a priority that are run
+//! at program exit. The priority only orders those closures between themselves: they are all run
+//! from `atexit`, before any destructor.
+//!
+//! `DropRegistry` is a thin wrapper over `at_exit` that drops registered objects with the
+//! priority of the registry, and `register_exit_hook` notifies a `&'static dyn ExitHook` the same
+//! way.
+//!
+//! # Runtime only build
+//!
+//! The `constructor`, `destructor` and `dynamic` attributes are provided by the default feature
+//! `macros`. With `default-features = false` the crate provides only its runtime types and does
+//! not depend on the proc macro crate and its dependencies.
+//!
+//! # Minimum supported Rust version
+//!
+//! This crate requires Rust 1.65: `prewarm` uses `std::thread::scope` (1.63) and
+//! `Lazy::init_backtrace` uses `std::backtrace` (1.65).
 //!
 //! # Debuging initialization order
 //!
@@ -198,6 +254,15 @@
 //! circular dependencies will cause either a dead lock or an infinite loop. If the feature `debug_order` is
 //! enabled, atemp are made to detect those circular dependencies. In most case they will be detected.
 //!
+//! In debug mode, *lazy statics* also record the backtrace of the access that triggered their
+//! initialization, see `Lazy::init_backtrace`.
+//!
+//! With the feature `debug_drop_count`, in debug mode, dropping a static twice causes a panic and
+//! a destructor with priority `last` checks at program exit that every *dynamic static* and global
+//! *lazy static* whose drop was scheduled has been dropped. The check is skipped after
+//! `set_fast_exit(true)`, and statics dropped by destructors that run after it are reported as not
+//! dropped. On targets that do not support priorities, only double drops are detected.
+//!
 //! [1]: https://crates.io/crates/lazy_static
 
 #[doc(hidden)]
@@ -263,69 +328,324 @@
 ///  are placed in ".CRT$XPU" and those with a priority in `format!(".CRT$XPTZ{:05}",65535-p)`.
 mod details {}
 
-use core::mem::ManuallyDrop;
+// Code generated by the attributes refers to this crate as `::static_init`.
+extern crate self as static_init;
+
+use core::panic::RefUnwindSafe;
+use core::sync::atomic::{AtomicBool, Ordering};
 
+#[cfg(feature = "macros")]
 #[doc(inline)]
 pub use static_init_macro::constructor;
 
+#[cfg(feature = "macros")]
 #[doc(inline)]
 pub use static_init_macro::destructor;
 
+/// Register a block of code to be run at program initialization (before main).
+///
+/// This is a shorthand for a function declared with the [macro@constructor] attribute. An
+/// optional priority can be given before the block, with the same meaning as the priority of
+/// [macro@constructor].
+///
+/// ```ignore
+/// use static_init::constructor_block;
+///
+/// constructor_block!{
+///     // run before main start
+/// }
+///
+/// constructor_block!{ 10 =>
+///     // run before the block above
+/// }
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! constructor_block {
+    ($priority:literal => $($body:tt)*) => {
+        const _: () = {
+            #[$crate::constructor($priority)]
+            extern "C" fn __static_init_constructor_block() {
+                $($body)*
+            }
+        };
+    };
+    ($($body:tt)*) => {
+        const _: () = {
+            #[$crate::constructor]
+            extern "C" fn __static_init_constructor_block() {
+                $($body)*
+            }
+        };
+    };
+}
+
+#[cfg(feature = "macros")]
 #[doc(inline)]
 pub use static_init_macro::dynamic;
 
+pub mod priority;
+
+pub mod sections;
+
+pub mod caps;
+
 #[cfg(feature = "lazy")]
 mod static_lazy;
 
 #[cfg(feature = "lazy")]
-pub use static_lazy::{Lazy,ConstLazy};
+pub use static_lazy::{Lazy,ConstLazy,AnyLazyStatic,prewarm};
+
+#[cfg(all(feature = "lazy", feature = "test_override"))]
+pub use static_lazy::OverrideGuard;
+
+#[cfg(feature = "lazy")]
+pub mod debug;
+
+#[cfg(feature = "lazy")]
+mod once;
+
+#[cfg(feature = "lazy")]
+pub use once::{Once, OnceState};
+
+#[cfg(feature = "lazy")]
+mod interner;
+
+#[cfg(feature = "lazy")]
+pub use interner::{intern_str, Internable, Interner, __InternedLiteral};
+
+#[cfg(feature = "lazy")]
+mod lazy_slab;
+
+#[cfg(feature = "lazy")]
+pub use lazy_slab::LazySlab;
+
+#[cfg(feature = "lazy")]
+mod split_lazy;
+
+#[cfg(feature = "lazy")]
+pub use split_lazy::SplitLazy;
+
+#[cfg(all(feature = "per_cpu", target_os = "linux"))]
+mod per_cpu;
+
+#[cfg(all(feature = "per_cpu", target_os = "linux"))]
+pub use per_cpu::PerCpuLazy;
 
 mod thread_local_lazy;
 
-pub use thread_local_lazy::{Lazy as ThreadLocalLazy, ConstLazy as ThreadLocalConstLazy};
+pub use thread_local_lazy::{Lazy as ThreadLocalLazy, ConstLazy as ThreadLocalConstLazy, AccessError};
 
 #[cfg(feature = "thread_local_drop")]
-pub use thread_local_lazy::__push_tls_destructor;
+pub use thread_local_lazy::{__leak_late_tls, __push_tls_destructor};
 
-union StaticBase<T> {
-    k: (),
-    v: ManuallyDrop<T>,
-}
+mod constructor_error;
 
-#[derive(Debug)]
+pub use constructor_error::{
+    constructor_error_count, set_constructor_error_handler, ConstructorError,
+    ConstructorErrorHandler, __constructor_error,
+};
+
+#[cfg(any(feature = "alloc", feature = "atexit"))]
+mod exit_handlers;
+
+#[cfg(feature = "alloc")]
+pub use exit_handlers::{push_exit_handler, run_exit_handlers};
+
+#[cfg(feature = "atexit")]
+pub use exit_handlers::{at_exit, register_exit_hook, DropRegistry, ExitHook, RegistrationClosed};
+
+#[cfg(feature = "atexit")]
 #[doc(hidden)]
+#[inline(always)]
+pub fn __atexit(f: extern "C" fn()) {
+    unsafe { libc::atexit(f) };
+}
+
+/// Register a block of code to be run at program exit.
+///
+/// The block is registered with `atexit` each time the macro invocation is executed. Blocks
+/// are run in the reverse order of their registration. As the block is the body of a function,
+/// it cannot capture local variables.
+///
+/// ```
+/// use static_init::defer_at_exit;
+///
+/// defer_at_exit!{
+///     println!("bye");
+/// }
+/// ```
+#[cfg(feature = "atexit")]
+#[macro_export]
+macro_rules! defer_at_exit {
+    ($($body:tt)*) => {{
+        extern "C" fn __static_init_deferred() {
+            $($body)*
+        }
+        $crate::__atexit(__static_init_deferred)
+    }};
+}
+
+/// Register a block of code to be run at the exit of the current thread.
+///
+/// The block is run with the drops of the *thread local lazy statics* of the thread, in the
+/// order of the registrations. As the block is the body of a function, it cannot capture
+/// local variables.
+///
+/// The macro evaluates to `false` if it is executed while the thread local destructors of the
+/// thread are running, in which case the block is not run.
+///
+/// ```
+/// use static_init::defer_at_thread_exit;
+///
+/// std::thread::spawn(|| {
+///     defer_at_thread_exit!{
+///         println!("thread exit");
+///     };
+/// }).join().unwrap();
+/// ```
+#[cfg(feature = "thread_local_drop")]
+#[macro_export]
+macro_rules! defer_at_thread_exit {
+    ($($body:tt)*) => {{
+        fn __static_init_deferred() {
+            $($body)*
+        }
+        unsafe { $crate::__push_tls_destructor(__static_init_deferred) }
+    }};
+}
+
+static FAST_EXIT: AtomicBool = AtomicBool::new(false);
+
+/// Skip the drop of *dynamic statics* and *lazy statics* at program exit.
+///
+/// After `set_fast_exit(true)`, the drops registered by the `dynamic` attribute return without
+/// dropping the static. This is intended for exit paths where running global destructors is
+/// unsafe (after a crash for example). Functions declared with the `destructor` attribute and
+/// thread local drops are still run.
+#[inline]
+pub fn set_fast_exit(v: bool) {
+    FAST_EXIT.store(v, Ordering::Release)
+}
+
+/// Return `true` if drops of statics at program exit are skipped, see [set_fast_exit].
+#[inline]
+pub fn is_fast_exit() -> bool {
+    FAST_EXIT.load(Ordering::Acquire)
+}
+
+static FINALIZATION_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Do not register the drop of *lazy statics* initialized after this call.
+///
+/// *Lazy statics* declared with `#[dynamic(lazy,drop)]` register their drop with `atexit` when
+/// they are initialized. After this call, the registration is skipped, so those statics are never
+/// dropped. This is intended for short lived invocations of a program, and should be called early.
+#[inline]
+pub fn disable_finalization() {
+    FINALIZATION_DISABLED.store(true, Ordering::Release)
+}
+
+/// Returns whether finalization is currently disabled (see [disable_finalization] and
+/// [Config]).
+#[inline]
+pub fn is_finalization_disabled() -> bool {
+    FINALIZATION_DISABLED.load(Ordering::Acquire)
+}
+
+mod config;
+
+pub use config::Config;
+
+#[cfg(debug_mode)]
+mod drop_count;
+
+mod static_storage;
+
+pub use static_storage::StaticStorage;
+
+/// How a *dynamic static* is initialized.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum InitMode {
+    /// Initialized by a const expression (`#[dynamic(drop_only=<prio>)]`).
     Const,
+    /// Initialized on first access, or before main for *lesser lazy statics*
+    /// (`#[dynamic(lazy)]`).
     Lazy,
+    /// Initialized at program startup by a constructor of the given priority
+    /// (`#[dynamic(init=<prio>)]`).
     Dynamic(u16),
 }
 
-#[derive(Debug)]
-#[doc(hidden)]
+/// How a *dynamic static* is dropped.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum DropMode {
+    /// Never dropped.
     None,
+    /// Dropped by a function registered with `atexit`, or at thread exit for thread locals
+    /// (`#[dynamic(drop)]`).
     AtExit,
+    /// Dropped by a destructor of the given priority (`#[dynamic(drop=<prio>)]`).
     Dynamic(u16),
 }
 
 
+/// The concrete type used by the `dynamic` macro to wrap a static.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StaticFlavor {
+    /// A mutable *dynamic static*, wrapped in [Static].
+    Static,
+    /// A non mutable *dynamic static*, wrapped in [ConstStatic].
+    ConstStatic,
+    /// A *lazy static*, wrapped in [Lazy].
+    Lazy,
+    /// A non mutable *lazy static* that is dropped, wrapped in [ConstLazy].
+    ConstLazy,
+    /// A thread local *lazy static*, wrapped in [ThreadLocalLazy].
+    ThreadLocalLazy,
+    /// A non mutable thread local *lazy static* that is dropped, wrapped in
+    /// [ThreadLocalConstLazy].
+    ThreadLocalConstLazy,
+}
+
+/// Informations about a *dynamic static* declaration.
+///
+/// It is only recorded in debug mode, see the `info` associated function
+/// of the static types.
 #[derive(Debug)]
-#[doc(hidden)]
 pub struct StaticInfo {
+    /// The name of the static.
     pub variable_name: &'static str,
+    /// The file where the static is declared.
     pub file_name:     &'static str,
+    /// The line of the declaration.
     pub line:          u32,
+    /// The column of the declaration.
     pub column:        u32,
-    pub init_mode: InitMode,
-    pub drop_mode: DropMode,
+    /// The type of the static as it is written in its declaration.
+    ///
+    /// Aliases and paths are not resolved: `StaticInfo` is built in a const context, where
+    /// `core::any::type_name` is not available.
+    pub type_name:     &'static str,
+    /// The size of the static value.
+    pub size:          usize,
+    /// The type that wraps the static.
+    pub flavor:        StaticFlavor,
+    /// How the static is initialized.
+    pub init_mode:     InitMode,
+    /// How the static is dropped.
+    pub drop_mode:     DropMode,
 }
 
 pub use static_impl::{Static, ConstStatic,__set_init_prio};
 
+// The value of dynamic statics is only written during program initialization.
+impl<T: RefUnwindSafe> RefUnwindSafe for Static<T> {}
+impl<T: RefUnwindSafe> RefUnwindSafe for ConstStatic<T> {}
+
 #[cfg(debug_mode)]
 mod static_impl {
-    use super::{StaticBase,StaticInfo,InitMode,DropMode};
-    use core::mem::ManuallyDrop;
+    use super::{drop_count,StaticStorage,StaticInfo,InitMode,DropMode};
     use core::ops::{Deref,DerefMut};
     use core::cell::UnsafeCell;
   /// The actual type of mutable *dynamic statics*.
@@ -335,7 +655,7 @@ mod static_impl {
   /// All associated functions are only usefull for the implementation of
   /// the `dynamic` proc macro attribute
   pub struct Static<T>(
-      StaticBase<T>,
+      StaticStorage<T>,
       StaticInfo,
       AtomicI32,
   );
@@ -365,35 +685,60 @@ mod static_impl {
   impl<T> Static<T> {
       #[inline]
       pub const fn uninit(info: StaticInfo) -> Self {
-              Self(StaticBase { k: () }, info, AtomicI32::new(0))
+              Self(StaticStorage::uninit(), info, AtomicI32::new(0))
+      }
+      /// Return the information recorded about this static.
+      #[inline]
+      pub fn info(this: &Self) -> Option<&StaticInfo> {
+          Some(&this.1)
       }
       #[inline]
       pub const fn from(v: T, info: StaticInfo) -> Self {
-              Static(
-                  StaticBase {
-                      v: ManuallyDrop::new(v),
-                  },
-                  info,
-                  AtomicI32::new(1),
-              )
+              Static(StaticStorage::new(v), info, AtomicI32::new(1))
+      }
+      /// Return a pointer to the value of the static.
+      ///
+      /// The address is known at link time, so the pointer can be handed to C code before the
+      /// static is initialized: the constructor of the static then writes the value in place.
+      #[inline]
+      pub const fn as_ptr(this: &Self) -> *mut T {
+          this.0.as_ptr()
       }
   
+      /// Set the value of the static.
+      ///
+      /// # Safety
+      ///
+      /// The static shall be in an uninitialized state.
       #[inline]
       pub unsafe fn set_to(this: &mut Self, v: T) {
-              this.0.v = ManuallyDrop::new(v);
+              this.0.write(v);
               this.2.store(1, Ordering::Relaxed);
+              if this.1.drop_mode != DropMode::None {
+                  drop_count::drop_registered();
+              }
       }
   
+      /// Drop the value of the static.
+      ///
+      /// # Safety
+      ///
+      /// The static shall be in an initialized state and shall not be accessed any more.
       #[inline]
       pub unsafe fn drop(this: &mut Self) {
+              drop_count::assert_not_dropped(Some(&this.1), this.2.load(Ordering::Relaxed) == 2);
               if let DropMode::Dynamic(prio) = &this.1.drop_mode {
                   CUR_DROP_PRIO.store(*prio as i32, Ordering::Relaxed);
-                  ManuallyDrop::drop(&mut this.0.v);
+                  this.0.drop_in_place();
                   CUR_DROP_PRIO.store(i32::MIN, Ordering::Relaxed);
               } else {
-                  ManuallyDrop::drop(&mut this.0.v);
+                  this.0.drop_in_place();
               };
               this.2.store(2, Ordering::Relaxed);
+              // const initialized statics are not counted
+              if this.1.init_mode != InitMode::Const {
+                  drop_count::drop_unregistered();
+              }
       }
   }
   
@@ -402,7 +747,7 @@ mod static_impl {
       if status == 0 {
           core::panic!(
               "Attempt to access variable {:#?} before it is initialized during initialization \
-               priority {}. Tip: increase init priority of this static to a value larger than \
+               priority {prio}. Tip: increase init priority of this static to a value larger than \
                {prio} (attribute syntax: `#[dynamic(init=<prio>)]`)",
               info,
               prio = CUR_INIT_PRIO.load(Ordering::Relaxed)
@@ -466,14 +811,14 @@ mod static_impl {
       #[inline(always)]
       fn deref(&self) -> &T {
           check_access(&self.1, self.2.load(Ordering::Relaxed));
-          unsafe { &*self.0.v }
+          unsafe { &*self.0.as_ptr() }
       }
   }
   impl<T> DerefMut for Static<T> {
       #[inline(always)]
       fn deref_mut(&mut self) -> &mut T {
           check_access(&self.1, self.2.load(Ordering::Relaxed));
-          unsafe { &mut *self.0.v }
+          unsafe { &mut *self.0.as_ptr() }
       }
   }
 
@@ -482,14 +827,36 @@ mod static_impl {
         pub const fn uninit(info: StaticInfo) -> Self {
             Self(UnsafeCell::new(Static::uninit(info)))
         }
+        /// Return the information recorded about this static.
+        #[inline]
+        pub fn info(this: &Self) -> Option<&StaticInfo> {
+            Static::info(unsafe { &*this.0.get() })
+        }
         #[inline]
         pub const fn from(v: T, info: StaticInfo) -> Self {
             Self(UnsafeCell::new(Static::from(v, info)))
         }
+        /// Return a pointer to the value of the static.
+        ///
+        /// See [Static::as_ptr].
+        #[inline]
+        pub const fn as_ptr(this: &Self) -> *const T {
+            Static::as_ptr(unsafe { &*(this.0.get() as *const Static<T>) })
+        }
+        /// Set the value of the static.
+        ///
+        /// # Safety
+        ///
+        /// The static shall be in an uninitialized state.
         #[inline]
         pub unsafe fn set_to(this: &Self, v: T) {
             Static::set_to(&mut (*this.0.get()), v)
         }
+        /// Drop the value of the static.
+        ///
+        /// # Safety
+        ///
+        /// The static shall be in an initialized state and shall not be accessed any more.
         #[inline]
         pub unsafe fn drop(this: &Self) {
             Static::drop(&mut *this.0.get());
@@ -503,16 +870,15 @@ mod static_impl {
         type Target = T;
         #[inline(always)]
         fn deref(&self) -> &T {
-            unsafe { &**self.0.get() }
+            unsafe { &*self.0.get() }
         }
     }
 }
 
 #[cfg(not(debug_mode))]
 mod static_impl {
-  use core::mem::ManuallyDrop;
   use core::ops::{Deref,DerefMut};
-  use super::StaticBase;
+  use super::{StaticStorage,StaticInfo};
     use core::cell::UnsafeCell;
   /// The actual type of mutable *dynamic statics*.
   ///
@@ -521,7 +887,7 @@ mod static_impl {
   /// All associated functions are only usefull for the implementation of
   /// the `dynamic` proc macro attribute
   pub struct Static<T>(
-      StaticBase<T>,
+      StaticStorage<T>,
   );
 
   /// The actual type of non mutable *dynamic statics*.
@@ -542,23 +908,46 @@ mod static_impl {
   impl<T> Static<T> {
       #[inline]
       pub const fn uninit() -> Self {
-          Self(StaticBase { k: () })
+          Self(StaticStorage::uninit())
+      }
+      /// Return the information recorded about this static.
+      ///
+      /// Always `None` when not in debug mode.
+      #[inline]
+      pub fn info(_: &Self) -> Option<&StaticInfo> {
+          None
       }
       #[inline]
       pub const fn from(v: T) -> Self {
-         Static(StaticBase {
-             v: ManuallyDrop::new(v),
-         })
+         Static(StaticStorage::new(v))
+      }
+      /// Return a pointer to the value of the static.
+      ///
+      /// The address is known at link time, so the pointer can be handed to C code before the
+      /// static is initialized: the constructor of the static then writes the value in place.
+      #[inline]
+      pub const fn as_ptr(this: &Self) -> *mut T {
+          this.0.as_ptr()
       }
   
+      /// Set the value of the static.
+      ///
+      /// # Safety
+      ///
+      /// The static shall be in an uninitialized state.
       #[inline]
       pub unsafe fn set_to(this: &mut Self, v: T) {
-          this.0.v = ManuallyDrop::new(v);
+          this.0.write(v);
       }
   
+      /// Drop the value of the static.
+      ///
+      /// # Safety
+      ///
+      /// The static shall be in an initialized state and shall not be accessed any more.
       #[inline]
       pub unsafe fn drop(this: &mut Self) {
-              ManuallyDrop::drop(&mut this.0.v);
+              this.0.drop_in_place();
       }
   }
   
@@ -566,13 +955,13 @@ mod static_impl {
       type Target = T;
       #[inline(always)]
       fn deref(&self) -> &T {
-          unsafe { &*self.0.v }
+          unsafe { &*self.0.as_ptr() }
       }
   }
   impl<T> DerefMut for Static<T> {
       #[inline(always)]
       fn deref_mut(&mut self) -> &mut T {
-          unsafe { &mut *self.0.v }
+          unsafe { &mut *self.0.as_ptr() }
       }
   }
 
@@ -581,14 +970,36 @@ mod static_impl {
         pub const fn uninit() -> Self {
             Self(UnsafeCell::new(Static::uninit()))
         }
+        /// Return the information recorded about this static.
+        #[inline]
+        pub fn info(this: &Self) -> Option<&StaticInfo> {
+            Static::info(unsafe { &*this.0.get() })
+        }
         #[inline]
         pub const fn from(v: T) -> Self {
             Self(UnsafeCell::new(Static::from(v)))
         }
+        /// Return a pointer to the value of the static.
+        ///
+        /// See [Static::as_ptr].
+        #[inline]
+        pub const fn as_ptr(this: &Self) -> *const T {
+            Static::as_ptr(unsafe { &*(this.0.get() as *const Static<T>) })
+        }
+        /// Set the value of the static.
+        ///
+        /// # Safety
+        ///
+        /// The static shall be in an uninitialized state.
         #[inline]
         pub unsafe fn set_to(this: &Self, v: T) {
             Static::set_to(&mut (*this.0.get()), v)
         }
+        /// Drop the value of the static.
+        ///
+        /// # Safety
+        ///
+        /// The static shall be in an initialized state and shall not be accessed any more.
         #[inline]
         pub unsafe fn drop(this: &Self) {
             Static::drop(&mut *this.0.get());
@@ -602,7 +1013,7 @@ mod static_impl {
         type Target = T;
         #[inline(always)]
         fn deref(&self) -> &T {
-            unsafe { &**self.0.get() }
+            unsafe { &*self.0.get() }
         }
     }
 }
@@ -0,0 +1,77 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use core::fmt;
+
+/// A synchronization primitive to run a one time initialization.
+///
+/// It has the same interface as `std::sync::Once` and is implemented with the same
+/// primitive as *lazy statics*.
+pub struct Once(parking_lot::Once);
+
+/// State passed to the closure of [Once::call_once_force].
+#[derive(Debug)]
+pub struct OnceState {
+    poisoned: bool,
+}
+
+impl OnceState {
+    /// Return true if a previous call to the `Once` panicked.
+    #[inline]
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+}
+
+impl Once {
+    /// Create a new `Once`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(parking_lot::Once::new())
+    }
+
+    /// Run `f` if it is the first call to a `call_once*` function of this `Once` that
+    /// completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a previous call panicked (the `Once` is poisoned).
+    #[inline]
+    pub fn call_once(&self, f: impl FnOnce()) {
+        self.0.call_once(f)
+    }
+
+    /// Same as [Once::call_once] but `f` is also run if the `Once` is poisoned.
+    #[inline]
+    pub fn call_once_force(&self, f: impl FnOnce(&OnceState)) {
+        self.0.call_once_force(|st| {
+            f(&OnceState {
+                poisoned: st.poisoned(),
+            })
+        })
+    }
+
+    /// Return true if a call to a `call_once*` function completed.
+    #[inline]
+    pub fn is_completed(&self) -> bool {
+        self.0.state().done()
+    }
+}
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Once {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Once")
+            .field("completed", &self.is_completed())
+            .finish()
+    }
+}
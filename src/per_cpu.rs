@@ -0,0 +1,83 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::LazySlab;
+use core::fmt;
+
+/// One lazily initialized value per CPU.
+///
+/// [PerCpuLazy::get] returns the value of the slot `cpu % N`, where `cpu` is the CPU the calling
+/// thread runs on as returned by `sched_getcpu`, and initializes it with the initializer given
+/// to [PerCpuLazy::new] on first access. Each slot is initialized independently, as the slots of
+/// a [LazySlab].
+///
+/// The thread may migrate to an other CPU right after the call, so the returned value is only
+/// an hint of locality: it must be safe to use from any CPU.
+///
+/// ```
+/// use static_init::PerCpuLazy;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static HITS: PerCpuLazy<AtomicUsize, 64> = PerCpuLazy::new(|| AtomicUsize::new(0));
+///
+/// HITS.get().fetch_add(1, Ordering::Relaxed);
+///
+/// let total: usize = HITS.iter().map(|c| c.load(Ordering::Relaxed)).sum();
+/// assert_eq!(total, 1);
+/// ```
+pub struct PerCpuLazy<T, const N: usize> {
+    slots: LazySlab<T, N>,
+    init:  fn() -> T,
+}
+
+impl<T, const N: usize> PerCpuLazy<T, N> {
+    /// Create a value per CPU whose slots are initialized with `init`.
+    pub const fn new(init: fn() -> T) -> Self {
+        Self {
+            slots: LazySlab::new(),
+            init,
+        }
+    }
+
+    /// Return the value of the slot of the current CPU, initializing it if needed.
+    ///
+    /// If the current CPU can not be known, the slot 0 is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is 0 or if a previous initialization of the slot panicked.
+    pub fn get(&self) -> &T {
+        self.slots.get_or_init(current_cpu() % N, self.init)
+    }
+
+    /// Return the value of slot `index` if it is initialized.
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    pub fn slot(&self, index: usize) -> Option<&T> {
+        self.slots.get(index)
+    }
+
+    /// Iterate over the values of the initialized slots.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..N).filter_map(move |i| self.slots.get(i))
+    }
+}
+
+fn current_cpu() -> usize {
+    let cpu = unsafe { libc::sched_getcpu() };
+    if cpu < 0 {
+        0
+    } else {
+        cpu as usize
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for PerCpuLazy<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.slots, f)
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Priorities of the initializations performed by the C and Rust runtimes.
+//!
+//! Priorities are expressed with the same semantic as the priority argument of
+//! [macro@crate::constructor] and of the `init` argument of [macro@crate::dynamic]:
+//! constructors with a higher priority are run first.
+//!
+//! Those priorities are only meaningfull on ELF plateforms. On windows, the standard library and
+//! the C runtime are initialized before any constructor is run, and on mac priorities are not
+//! supported.
+//!
+//! When the target is an ELF plateform, the `constructor` and `dynamic` attributes reject the
+//! priorities [RUST_ENV_CAPTURE] and [LIBSTDCXX_RESOURCES] because the order of a constructor
+//! of that priority relative to the runtime initialization would be unspecified.
+//!
+//! `#[constructor(first)]` is a shorthand for the priority [AFTER_LIBSTDCXX_RESOURCES] on ELF
+//! plateforms.
+//!
+//! ```ignore
+//! use static_init::constructor;
+//!
+//! // 65434 == AFTER_LIBSTDCXX_RESOURCES: std::env and C++ standard
+//! // library resources are usable.
+//! #[constructor(65434)]
+//! extern "C" fn early_init() {}
+//! ```
+
+/// Priority at which the rust standard library captures the program arguments and
+/// environment on gnu variants of ELF plateforms (section `.init_array.00099`).
+pub const RUST_ENV_CAPTURE: u16 = 65535 - 99;
+
+/// Constructors with this priority or an higher one are run before the rust standard library
+/// captures the program arguments and environment: `std::env::args` and `std::env::vars` will
+/// appear empty.
+pub const BEFORE_RUST_ENV_CAPTURE: u16 = RUST_ENV_CAPTURE + 1;
+
+/// Priority at which libstdc++ resources are initialized (section `.init_array.00100`).
+pub const LIBSTDCXX_RESOURCES: u16 = 65535 - 100;
+
+/// Highest priority at which constructors are run after the rust standard library captured
+/// the program arguments and environment.
+///
+/// Constructors with this priority or a lower one can use `std::env`.
+pub const AFTER_RUST_ENV_CAPTURE: u16 = AFTER_LIBSTDCXX_RESOURCES;
+
+/// Highest priority at which constructors are run after libstdc++ resources are initialized.
+///
+/// This is the earliest slot that is sequenced after all initializations performed by
+/// the C, C++ and Rust runtimes (gcc reserves the initialization priorities `0` to `100`
+/// of `.init_array.NNNNN` sections for the implementation).
+pub const AFTER_LIBSTDCXX_RESOURCES: u16 = LIBSTDCXX_RESOURCES - 1;
@@ -0,0 +1,150 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Names of the link sections used by [macro@crate::constructor] and [macro@crate::destructor].
+//!
+//! Crates that place function pointers in those sections by themselves can use these names to
+//! stay consistent with the ordering scheme of this crate.
+//!
+//! ```
+//! use static_init::sections;
+//!
+//! if cfg!(all(target_os = "linux", target_env = "gnu")) {
+//!     assert_eq!(sections::init_section(10).unwrap().as_str(), ".init_array.65525");
+//! }
+//! ```
+
+use core::fmt;
+
+/// ELF section of constructors without priority.
+pub const INIT_ARRAY: &str = ".init_array";
+
+/// ELF section of destructors without priority.
+pub const FINI_ARRAY: &str = ".fini_array";
+
+/// COFF section of C++ static initializers.
+pub const CRT_XCU: &str = ".CRT$XCU";
+
+/// COFF section of C++ static terminators.
+pub const CRT_XPU: &str = ".CRT$XPU";
+
+/// Prefix of the COFF sections of prioritized constructors.
+pub const CRT_XCTZ: &str = ".CRT$XCTZ";
+
+/// Prefix of the COFF sections of prioritized destructors.
+pub const CRT_XPTZ: &str = ".CRT$XPTZ";
+
+/// Mach-O section of constructors.
+pub const MOD_INIT_FUNC: &str = "__DATA,__mod_init_func";
+
+/// Mach-O section of destructors.
+pub const MOD_TERM_FUNC: &str = "__DATA,__mod_term_func";
+
+const MAX_LEN: usize = 32;
+
+/// A section name built at compile time.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct SectionName {
+    bytes: [u8; MAX_LEN],
+    len:   usize,
+}
+
+impl SectionName {
+    const fn from_str(s: &str) -> Self {
+        Self::empty().push_str(s)
+    }
+
+    const fn empty() -> Self {
+        Self {
+            bytes: [0; MAX_LEN],
+            len:   0,
+        }
+    }
+
+    const fn push_str(mut self, s: &str) -> Self {
+        let s = s.as_bytes();
+        assert!(self.len + s.len() <= MAX_LEN, "section name too long");
+        let mut i = 0;
+        while i < s.len() {
+            self.bytes[self.len + i] = s[i];
+            i += 1;
+        }
+        self.len += s.len();
+        self
+    }
+
+    /// The section name.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // only built from str and ascii digits
+        unsafe { core::str::from_utf8_unchecked(&self.bytes[..self.len]) }
+    }
+}
+
+impl fmt::Debug for SectionName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SectionName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Append the 5 digits of `65535 - priority` to `prefix`.
+///
+/// Sections with this suffix are sorted by the linker, so higher priorities come first.
+/// For example `prioritized_section(".init_array.", 10)` is `".init_array.65525"` and
+/// `prioritized_section(CRT_XCTZ, 10)` is `".CRT$XCTZ65525"`.
+pub const fn prioritized_section(prefix: &str, priority: u16) -> SectionName {
+    let mut n = SectionName::from_str(prefix);
+    assert!(n.len + 5 <= MAX_LEN, "section name too long");
+    let v = 65535 - priority;
+    let mut div = 10000;
+    while div > 0 {
+        n.bytes[n.len] = b'0' + ((v / div) % 10) as u8;
+        n.len += 1;
+        div /= 10;
+    }
+    n
+}
+
+/// The section in which [macro@crate::constructor] places constructors of priority `priority`
+/// on the current target.
+///
+/// Returns `None` if constructors are not supported or if the priority is not supported on this
+/// target.
+pub const fn init_section(priority: u16) -> Option<SectionName> {
+    if cfg!(elf) {
+        Some(prioritized_section(".init_array.", priority))
+    } else if cfg!(coff) {
+        Some(prioritized_section(CRT_XCTZ, priority))
+    } else if cfg!(mach_o) && priority == 0 {
+        Some(SectionName::from_str(MOD_INIT_FUNC))
+    } else {
+        None
+    }
+}
+
+/// The section in which [macro@crate::destructor] places destructors of priority `priority`
+/// on the current target.
+///
+/// Returns `None` if destructors are not supported or if the priority is not supported on this
+/// target.
+pub const fn fini_section(priority: u16) -> Option<SectionName> {
+    if cfg!(elf) {
+        Some(prioritized_section(".fini_array.", priority))
+    } else if cfg!(coff) {
+        Some(prioritized_section(CRT_XPTZ, priority))
+    } else if cfg!(mach_o) && priority == 0 {
+        Some(SectionName::from_str(MOD_TERM_FUNC))
+    } else {
+        None
+    }
+}
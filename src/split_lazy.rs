@@ -0,0 +1,107 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::StaticStorage;
+use core::cell::Cell;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use parking_lot::Once;
+
+/// A *lazy static* whose value is stored in an other static.
+///
+/// The initialization state is stored in the `SplitLazy` and the value in the
+/// [StaticStorage] given to [SplitLazy::new], so that both can be placed in different link
+/// sections. This is the type of the statics declared with
+/// `#[dynamic(lazy, link_section = "<section>")]`.
+///
+/// Unlike [crate::Lazy], it does not record debug information and can not be dropped.
+pub struct SplitLazy<T: 'static, F = fn() -> T> {
+    value:  &'static StaticStorage<T>,
+    initer: Once,
+    init:   Cell<Option<F>>,
+}
+
+unsafe impl<T: Send + Sync, F: Send> Sync for SplitLazy<T, F> {}
+
+impl<T, F> SplitLazy<T, F> {
+    /// Create a lazy that stores its value in `value`, initialized with `f`.
+    ///
+    /// `value` shall not be used by anything else.
+    pub const fn new(value: &'static StaticStorage<T>, f: F) -> Self {
+        Self {
+            value,
+            initer: Once::new(),
+            init: Cell::new(Some(f)),
+        }
+    }
+
+    /// Return a pointer to the value.
+    ///
+    /// The value may be in an uninitialized state.
+    #[inline(always)]
+    pub const fn as_mut_ptr(this: &Self) -> *mut T {
+        this.value.as_ptr()
+    }
+
+    /// Return true if the value has been initialized.
+    #[inline(always)]
+    pub fn is_initialized(this: &Self) -> bool {
+        this.initer.state().done()
+    }
+
+    /// Ensure the value is initialized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a previous initialization panicked.
+    #[inline(always)]
+    pub fn ensure_init(this: &Self)
+    where
+        F: FnOnce() -> T,
+    {
+        this.initer
+            .call_once(|| unsafe { this.value.write(this.init.take().unwrap()()) })
+    }
+
+    /// Ensure the value is initialized.
+    ///
+    /// This is intended to be used at program start up by the dynamic macro.
+    #[inline(always)]
+    pub fn __do_init(this: &Self)
+    where
+        F: FnOnce() -> T,
+    {
+        Self::ensure_init(this)
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for SplitLazy<T, F> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        Self::ensure_init(self);
+        unsafe { &*Self::as_mut_ptr(self) }
+    }
+}
+
+impl<T, F: FnOnce() -> T> DerefMut for SplitLazy<T, F> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        Self::ensure_init(self);
+        unsafe { &mut *Self::as_mut_ptr(self) }
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for SplitLazy<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("SplitLazy");
+        if Self::is_initialized(self) {
+            d.field("value", unsafe { &*Self::as_mut_ptr(self) });
+        }
+        d.finish_non_exhaustive()
+    }
+}
@@ -1,9 +1,146 @@
-#[cfg(debug_mode)]
-use super::StaticInfo;
+use super::{StaticInfo, StaticStorage};
+#[cfg(feature = "test_override")]
+use core::{marker::PhantomData, mem::ManuallyDrop, ptr};
+use core::panic::{RefUnwindSafe, UnwindSafe};
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 pub use lazy_impl::{ConstLazy, Lazy};
 
-#[cfg(all(support_priority, not(feature = "test_no_global_lazy_hint")))]
+// A panic during initialization leaves the lazy uninitialized and
+// any later access panics, as for std::sync::LazyLock.
+impl<T: RefUnwindSafe + UnwindSafe, F: UnwindSafe> RefUnwindSafe for Lazy<T, F> {}
+
+/// Object safe access to *lazy statics* of any type.
+///
+/// It is implemented by [Lazy] and [ConstLazy] so that statics of different types can be
+/// gathered, for example in a `&'static [&'static dyn AnyLazyStatic]`, and driven by generic
+/// initialization or shutdown code.
+pub trait AnyLazyStatic: Sync {
+    /// Return true if the value has been initialized.
+    fn is_initialized(&self) -> bool;
+    /// Ensure the value is initialized.
+    fn force(&self);
+    /// Return the information recorded about this static.
+    ///
+    /// Always `None` when not in debug mode.
+    fn info(&self) -> Option<&StaticInfo>;
+    /// Drop the contained value.
+    ///
+    /// # Safety
+    ///
+    /// The value shall be initialized and shall not be accessed any more.
+    unsafe fn drop_value(&self);
+}
+
+impl<T: Sync, F: FnOnce() -> T> AnyLazyStatic for Lazy<T, F> {
+    fn is_initialized(&self) -> bool {
+        Lazy::is_initialized(self)
+    }
+    fn force(&self) {
+        Lazy::ensure_init(self)
+    }
+    fn info(&self) -> Option<&StaticInfo> {
+        Lazy::info(self)
+    }
+    unsafe fn drop_value(&self) {
+        Lazy::drop(self)
+    }
+}
+
+impl<T: Sync, F: FnOnce() -> T> AnyLazyStatic for ConstLazy<T, F> {
+    fn is_initialized(&self) -> bool {
+        ConstLazy::is_initialized(self)
+    }
+    fn force(&self) {
+        ConstLazy::ensure_init(self)
+    }
+    fn info(&self) -> Option<&StaticInfo> {
+        ConstLazy::info(self)
+    }
+    unsafe fn drop_value(&self) {
+        ConstLazy::drop(self)
+    }
+}
+
+impl<T, E, F: FnOnce() -> Result<T, E>> Lazy<Result<T, E>, F> {
+    /// Ensure the value is initialized and return a reference to the value or to the error of
+    /// a fallible initialization.
+    ///
+    /// The error is kept by the lazy, so every call returns the same error instead of
+    /// poisoning the static.
+    #[inline]
+    pub fn try_get(this: &Self) -> Result<&T, &E> {
+        (**this).as_ref()
+    }
+}
+
+impl<T, E, F: FnOnce() -> Result<T, E>> ConstLazy<Result<T, E>, F> {
+    /// See [Lazy::try_get].
+    #[inline]
+    pub fn try_get(this: &Self) -> Result<&T, &E> {
+        (**this).as_ref()
+    }
+}
+
+/// Guard returned by [Lazy::scoped_override] that restores the previous value of the lazy
+/// when it is dropped.
+#[cfg(feature = "test_override")]
+#[must_use = "the previous value is restored when the guard is dropped"]
+pub struct OverrideGuard<'a, T> {
+    ptr:     *mut T,
+    prev:    ManuallyDrop<T>,
+    phantom: PhantomData<&'a T>,
+}
+
+#[cfg(feature = "test_override")]
+impl<T> OverrideGuard<'_, T> {
+    unsafe fn new(ptr: *mut T, value: T) -> Self {
+        Self {
+            ptr,
+            prev: ManuallyDrop::new(ptr::replace(ptr, value)),
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "test_override")]
+impl<T> Drop for OverrideGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe { drop(ptr::replace(self.ptr, ManuallyDrop::take(&mut self.prev))) }
+    }
+}
+
+/// Initialize `statics` concurrently.
+///
+/// The statics are initialized by at most `std::thread::available_parallelism` threads. As a
+/// lazy initialization waits for the initialization of the lazies it depends on, dependencies
+/// between the statics are respected whatever their order in `statics`.
+///
+/// On targets that support constructor priorities, *lesser lazy statics* declared with the
+/// `dynamic` attribute are all initialized before main, so this is mainly useful on other targets.
+///
+/// # Panics
+///
+/// Panics if an initialization panics.
+pub fn prewarm(statics: &[&dyn AnyLazyStatic]) {
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(statics.len());
+    let next = AtomicUsize::new(0);
+    let work = || {
+        while let Some(l) = statics.get(next.fetch_add(1, Ordering::Relaxed)) {
+            l.force()
+        }
+    };
+    std::thread::scope(|s| {
+        for _ in 1..threads {
+            s.spawn(work);
+        }
+        work()
+    });
+}
+
+#[cfg(all(support_priority, feature = "macros", not(feature = "test_no_global_lazy_hint")))]
 mod inited {
 
     use core::sync::atomic::{AtomicBool, Ordering};
@@ -21,24 +158,29 @@ mod inited {
     }
 }
 
-#[cfg(all(support_priority, not(feature = "test_no_global_lazy_hint")))]
+#[cfg(all(support_priority, feature = "macros", not(feature = "test_no_global_lazy_hint")))]
 use inited::global_inited_hint;
 
 #[cfg(debug_mode)]
 mod lazy_impl {
-    use super::StaticInfo;
+    use super::{StaticInfo, StaticStorage};
+    use crate::drop_count;
+    #[cfg(feature = "test_override")]
+    use super::OverrideGuard;
     #[cfg(feature = "likely")]
     use likely_stable::unlikely;
 
-    #[cfg(all(support_priority, not(feature = "test_no_global_lazy_hint")))]
+    #[cfg(all(support_priority, feature = "macros", not(feature = "test_no_global_lazy_hint")))]
     use super::global_inited_hint;
 
-    use core::cell::Cell;
-    use core::cell::UnsafeCell;
+    use core::cell::{Cell, UnsafeCell};
+    use core::any::Any;
     use core::fmt;
-    use core::mem::MaybeUninit;
+    use core::panic::AssertUnwindSafe;
+    use std::backtrace::Backtrace;
+    use std::boxed::Box;
     use core::ops::{Deref, DerefMut};
-    use core::sync::atomic::{AtomicBool, Ordering};
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
     use parking_lot::{
         lock_api::GetThreadId, lock_api::RawMutex as _, RawMutex, RawThreadId, ReentrantMutex,
@@ -49,17 +191,38 @@ mod lazy_impl {
     struct DebugLazyState<F> {
         initer: Cell<Option<NonZeroUsize>>,
         function: Cell<Option<F>>,
+        poisoned: Cell<bool>,
+    }
+
+    // Record that the initialization panicked.
+    struct PoisonOnUnwind<'a>(&'a Cell<bool>);
+
+    impl Drop for PoisonOnUnwind<'_> {
+        fn drop(&mut self) {
+            if std::thread::panicking() {
+                self.0.set(true)
+            }
+        }
     }
 
+    static NEXT_INIT_RANK: AtomicUsize = AtomicUsize::new(1);
+
     /// The type of *lazy statics*.
     ///
     /// Statics that are initialized on first access.
+    ///
+    /// The initializer `F` can be any `FnOnce() -> T`. A `&'static dyn Fn() -> T` can be used
+    /// so that the type of the lazy does not depend on a closure type and so that many lazies
+    /// share the same initialization policy.
     pub struct Lazy<T, F = fn() -> T> {
-        value: UnsafeCell<MaybeUninit<T>>,
+        value: StaticStorage<T>,
         inited: AtomicBool,
         debug_initer: ReentrantMutex<DebugLazyState<F>>,
         info: Option<StaticInfo>,
         dropped: AtomicBool,
+        drop_registered: AtomicBool,
+        init_rank: AtomicUsize,
+        init_backtrace: UnsafeCell<Option<Backtrace>>,
     }
 
     /// The type of const *lesser lazy statics*.
@@ -79,7 +242,7 @@ mod lazy_impl {
         /// by the dynamic macro.
         pub const fn new(f: F, _info: StaticInfo) -> Self {
             Self {
-                value: UnsafeCell::new(MaybeUninit::uninit()),
+                value: StaticStorage::uninit(),
                 inited: AtomicBool::new(false),
                 debug_initer: ReentrantMutex::const_new(
                     RawMutex::INIT,
@@ -87,10 +250,39 @@ mod lazy_impl {
                     DebugLazyState {
                         initer: Cell::new(None),
                         function: Cell::new(Some(f)),
+                        poisoned: Cell::new(false),
+                    },
+                ),
+                info: Some(_info),
+                dropped: AtomicBool::new(false),
+                drop_registered: AtomicBool::new(false),
+                init_rank: AtomicUsize::new(0),
+                init_backtrace: UnsafeCell::new(None),
+            }
+        }
+
+        /// Create a lazy that is already initialized with `v`.
+        ///
+        /// This allows statics whose value is sometimes known at compile time to have the
+        /// same type as lazy statics. The initializer is never called.
+        pub const fn from_value(v: T, _info: StaticInfo) -> Self {
+            Self {
+                value: StaticStorage::new(v),
+                inited: AtomicBool::new(true),
+                debug_initer: ReentrantMutex::const_new(
+                    RawMutex::INIT,
+                    RawThreadId::INIT,
+                    DebugLazyState {
+                        initer: Cell::new(None),
+                        function: Cell::new(None),
+                        poisoned: Cell::new(false),
                     },
                 ),
                 info: Some(_info),
                 dropped: AtomicBool::new(false),
+                drop_registered: AtomicBool::new(false),
+                init_rank: AtomicUsize::new(0),
+                init_backtrace: UnsafeCell::new(None),
             }
         }
 
@@ -99,7 +291,7 @@ mod lazy_impl {
         /// The value may be in an uninitialized state.
         #[inline(always)]
         pub const fn as_mut_ptr(this: &Self) -> *mut T {
-            this.value.get() as *mut T
+            this.value.as_ptr()
         }
 
         /// Ensure the value is initialized without optimization check
@@ -113,6 +305,13 @@ mod lazy_impl {
         {
             if !this.inited.load(Ordering::Acquire) {
                 let l = this.debug_initer.lock();
+                if l.poisoned.get() {
+                    if let Some(info) = &this.info {
+                        core::panic!("The initialization of {:#?} panicked.", info);
+                    } else {
+                        core::panic!("The lazy initialization panicked.");
+                    }
+                }
                 if let Some(initer) = l.initer.get() {
                     if initer == RawThreadId.nonzero_thread_id() {
                         if let Some(info) = &this.info {
@@ -121,14 +320,17 @@ mod lazy_impl {
                             core::panic!("Recurcive lazy initialization.");
                         }
                     }
-                    return;
                 } else {
                     l.initer.set(Some(RawThreadId.nonzero_thread_id()));
+                    unsafe { *this.init_backtrace.get() = Some(Backtrace::capture()) };
+                    let poison = PoisonOnUnwind(&l.poisoned);
                     unsafe {
-                        (*this.value.get())
-                            .as_mut_ptr()
+                        this.value
                             .write(l.function.take().unwrap()())
                     };
+                    core::mem::forget(poison);
+                    this.init_rank
+                        .store(NEXT_INIT_RANK.fetch_add(1, Ordering::Relaxed), Ordering::Relaxed);
                     this.inited.store(true, Ordering::Release);
                 }
             }
@@ -144,7 +346,7 @@ mod lazy_impl {
         where
             F: FnOnce() -> T,
         {
-            #[cfg(all(support_priority, not(feature = "test_no_global_lazy_hint")))]
+            #[cfg(all(support_priority, feature = "macros", not(feature = "test_no_global_lazy_hint")))]
             {
                 #[cfg(feature = "likely")]
                 if unlikely(!global_inited_hint()) {
@@ -156,7 +358,7 @@ mod lazy_impl {
                 }
             }
 
-            #[cfg(not(all(support_priority, not(feature = "test_no_global_lazy_hint"))))]
+            #[cfg(not(all(support_priority, feature = "macros", not(feature = "test_no_global_lazy_hint"))))]
             Self::__do_init(this);
 
             if this.dropped.load(Ordering::Acquire) {
@@ -168,14 +370,122 @@ mod lazy_impl {
             }
         }
 
+        /// Ensure the value is initialized and return a reference to it, or return the payload
+        /// of the panic of the initializer.
+        ///
+        /// If the initializer panics, the lazy is poisoned: later accesses panic and later calls
+        /// to this function return the payload of the panic reporting the poisoning, a message
+        /// string, not the payload of the panic of the initializer.
+        pub fn force_with_unwind(this: &Self) -> Result<&T, Box<dyn Any + Send>>
+        where
+            F: FnOnce() -> T,
+        {
+            std::panic::catch_unwind(AssertUnwindSafe(|| Self::__do_init(this)))?;
+            Ok(unsafe { &*Self::as_mut_ptr(this) })
+        }
+
+        /// Return true if the value has been initialized.
+        #[inline(always)]
+        pub fn is_initialized(this: &Self) -> bool {
+            this.inited.load(Ordering::Acquire)
+        }
+
+        /// Return a reference to the value without checking that it is initialized.
+        ///
+        /// In debug mode, this function panics if the value is not initialized.
+        ///
+        /// # Safety
+        ///
+        /// The value must be initialized and not dropped.
+        #[inline(always)]
+        pub unsafe fn get_unchecked(this: &Self) -> &T {
+            if !Self::is_initialized(this) || this.dropped.load(Ordering::Acquire) {
+                if let Some(info) = &this.info {
+                    core::panic!(
+                        "Unchecked access to {:#?} while it is not initialized or dropped.",
+                        info
+                    );
+                } else {
+                    core::panic!("Unchecked access to a lazy static not initialized or dropped.");
+                }
+            }
+            &*Self::as_mut_ptr(this)
+        }
+
+        /// Replace the value by `value` until the returned guard is dropped, then restore the
+        /// previous value.
+        ///
+        /// This is intended to inject values in lazy statics from tests. The lazy is initialized
+        /// before its value is replaced. The overriding value is dropped when the guard is
+        /// dropped.
+        ///
+        /// # Safety
+        ///
+        /// The lazy shall not be dropped while the guard is alive. References to the value
+        /// obtained before the call shall not be used while the guard is alive, and references
+        /// obtained while the guard is alive shall not be used after it is dropped. No other
+        /// thread shall access the value during the call and during the drop of the guard.
+        #[cfg(feature = "test_override")]
+        pub unsafe fn scoped_override(this: &Self, value: T) -> OverrideGuard<'_, T>
+        where
+            F: FnOnce() -> T,
+        {
+            Self::ensure_init(this);
+            OverrideGuard::new(Self::as_mut_ptr(this), value)
+        }
+
+        /// Return the information recorded about this static.
+        #[inline]
+        pub fn info(this: &Self) -> Option<&StaticInfo> {
+            this.info.as_ref()
+        }
+
+        /// Return the backtrace of the access that triggered the initialization.
+        ///
+        /// The backtrace is only recorded in debug mode and is captured with
+        /// `Backtrace::capture`, so it is only non empty if backtraces are enabled through
+        /// the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables.
+        #[inline]
+        pub fn init_backtrace(this: &Self) -> Option<&Backtrace> {
+            if this.inited.load(Ordering::Acquire) {
+                unsafe { (*this.init_backtrace.get()).as_ref() }
+            } else {
+                None
+            }
+        }
+
+        /// Rank of the end of this lazy initialization among all lazy statics.
+        #[inline]
+        pub(crate) fn init_rank(this: &Self) -> Option<usize> {
+            if this.inited.load(Ordering::Acquire) {
+                Some(this.init_rank.load(Ordering::Relaxed))
+            } else {
+                None
+            }
+        }
+
         /// Drop the contained value
         ///
         /// # Safety
         ///
         /// The value should not be accessed any more.
         pub unsafe fn drop(this: &Self) {
+            drop_count::assert_not_dropped(this.info.as_ref(), this.dropped.load(Ordering::Relaxed));
             Self::as_mut_ptr(this).drop_in_place();
             this.dropped.store(true, Ordering::Relaxed);
+            if this.drop_registered.swap(false, Ordering::Relaxed) {
+                drop_count::drop_unregistered();
+            }
+        }
+
+        /// Record that the drop of the value is scheduled.
+        ///
+        /// This function is intended to be used internaly by the dynamic macro.
+        #[doc(hidden)]
+        #[inline]
+        pub fn __drop_registered(this: &Self) {
+            this.drop_registered.store(true, Ordering::Relaxed);
+            drop_count::drop_registered();
         }
     }
 
@@ -231,6 +541,14 @@ mod lazy_impl {
             Self(Lazy::new(f, info))
         }
 
+        /// Create a lazy that is already initialized with `v`.
+        ///
+        /// This allows statics whose value is sometimes known at compile time to have the
+        /// same type as lazy statics. The initializer is never called.
+        pub const fn from_value(v: T, info: StaticInfo) -> Self {
+            Self(Lazy::from_value(v, info))
+        }
+
         /// Return a pointer to the value.
         ///
         /// The value may be in an uninitialized state.
@@ -262,6 +580,84 @@ mod lazy_impl {
         {
             Lazy::ensure_init(&this.0)
         }
+
+        /// Ensure the value is initialized and return a reference to it, or return the payload
+        /// of the panic of the initializer.
+        ///
+        /// See [Lazy::force_with_unwind].
+        pub fn force_with_unwind(this: &Self) -> Result<&T, Box<dyn Any + Send>>
+        where
+            F: FnOnce() -> T,
+        {
+            Lazy::force_with_unwind(&this.0)
+        }
+
+        /// Return true if the value has been initialized.
+        #[inline(always)]
+        pub fn is_initialized(this: &Self) -> bool {
+            Lazy::is_initialized(&this.0)
+        }
+
+        /// Return a reference to the value without checking that it is initialized.
+        ///
+        /// In debug mode, this function panics if the value is not initialized.
+        ///
+        /// # Safety
+        ///
+        /// The value must be initialized and not dropped.
+        #[inline(always)]
+        pub unsafe fn get_unchecked(this: &Self) -> &T {
+            Lazy::get_unchecked(&this.0)
+        }
+
+        /// Replace the value by `value` until the returned guard is dropped.
+        ///
+        /// See [Lazy::scoped_override].
+        ///
+        /// # Safety
+        ///
+        /// See [Lazy::scoped_override].
+        #[cfg(feature = "test_override")]
+        pub unsafe fn scoped_override(this: &Self, value: T) -> OverrideGuard<'_, T>
+        where
+            F: FnOnce() -> T,
+        {
+            Lazy::scoped_override(&this.0, value)
+        }
+
+        /// Return the information recorded about this static.
+        #[inline]
+        pub fn info(this: &Self) -> Option<&StaticInfo> {
+            Lazy::info(&this.0)
+        }
+
+        /// Return the backtrace of the access that triggered the initialization.
+        ///
+        /// See [Lazy::init_backtrace].
+        #[inline]
+        pub fn init_backtrace(this: &Self) -> Option<&Backtrace> {
+            Lazy::init_backtrace(&this.0)
+        }
+
+        #[inline]
+        pub(crate) fn init_rank(this: &Self) -> Option<usize> {
+            Lazy::init_rank(&this.0)
+        }
+
+        /// Drop the contained value
+        ///
+        /// # Safety
+        ///
+        /// The value should not be accessed any more.
+        pub unsafe fn drop(this: &Self) {
+            Lazy::drop(&this.0)
+        }
+
+        #[doc(hidden)]
+        #[inline(always)]
+        pub fn __drop_registered(this: &Self) {
+            Lazy::__drop_registered(&this.0)
+        }
     }
 
     impl<T, F> Deref for ConstLazy<T, F>
@@ -281,28 +677,39 @@ mod lazy_impl {
 
 #[cfg(not(debug_mode))]
 mod lazy_impl {
-    #[cfg(all(support_priority, not(feature = "test_no_global_lazy_hint")))]
+    #[cfg(all(support_priority, feature = "macros", not(feature = "test_no_global_lazy_hint")))]
     use super::global_inited_hint;
 
     #[cfg(feature = "likely")]
     use likely_stable::unlikely;
 
     use core::cell::Cell;
-    use core::cell::UnsafeCell;
     use core::fmt;
-    use core::hint::unreachable_unchecked;
-    use core::mem::MaybeUninit;
+    use core::any::Any;
     use core::ops::{Deref, DerefMut};
+    use core::panic::AssertUnwindSafe;
+    use std::backtrace::Backtrace;
+    use std::boxed::Box;
 
     use parking_lot::Once;
 
+    use super::{StaticInfo, StaticStorage};
+    #[cfg(feature = "test_override")]
+    use super::OverrideGuard;
+
     /// The type of *lesser lazy statics*.
     ///
     /// Statics that are initialized on first access or
     /// before main is called.
+    ///
+    /// The initializer `F` can be any `FnOnce() -> T`. A `&'static dyn Fn() -> T` can be used
+    /// so that the type of the lazy does not depend on a closure type and so that many lazies
+    /// share the same initialization policy.
     pub struct Lazy<T, F = fn() -> T> {
-        value: UnsafeCell<MaybeUninit<T>>,
+        value: StaticStorage<T>,
         initer: Once,
+        // set by from_value, only read by is_initialized
+        pre_initialized: bool,
         init_exp: Cell<Option<F>>,
     }
     /// The type of const *lesser lazy statics*.
@@ -319,18 +726,32 @@ mod lazy_impl {
         /// Initialize a lazy with a builder as argument.
         pub const fn new(f: F) -> Self {
             Self {
-                value: UnsafeCell::new(MaybeUninit::uninit()),
+                value: StaticStorage::uninit(),
                 initer: Once::new(),
+                pre_initialized: false,
                 init_exp: Cell::new(Some(f)),
             }
         }
 
+        /// Create a lazy that is already initialized with `v`.
+        ///
+        /// This allows statics whose value is sometimes known at compile time to have the
+        /// same type as lazy statics. The initializer is never called.
+        pub const fn from_value(v: T) -> Self {
+            Self {
+                value: StaticStorage::new(v),
+                initer: Once::new(),
+                pre_initialized: true,
+                init_exp: Cell::new(None),
+            }
+        }
+
         /// Return a pointer to the value.
         ///
         /// The value may be in an uninitialized state.
         #[inline(always)]
         pub const fn as_mut_ptr(this: &Self) -> *mut T {
-            this.value.get() as *mut T
+            this.value.as_ptr()
         }
 
         /// Ensure the value is initialized without optimization check
@@ -345,11 +766,10 @@ mod lazy_impl {
             //The compiler fails to automatically choose
             //which branch is the best one...
             this.initer.call_once(|| unsafe {
-                (*this.value.get()).as_mut_ptr().write(this
-                    .init_exp
-                    .take()
-                    .unwrap_or_else(|| unreachable_unchecked())(
-                ));
+                // no initializer: the value was given to from_value
+                if let Some(f) = this.init_exp.take() {
+                    this.value.write(f());
+                }
             });
         }
         /// Ensure the value is initialized without optimization check
@@ -363,7 +783,7 @@ mod lazy_impl {
         where
             F: FnOnce() -> T,
         {
-            #[cfg(all(support_priority, not(feature = "test_no_global_lazy_hint")))]
+            #[cfg(all(support_priority, feature = "macros", not(feature = "test_no_global_lazy_hint")))]
             {
                 #[cfg(feature = "likely")]
                 if unlikely(!global_inited_hint()) {
@@ -375,9 +795,86 @@ mod lazy_impl {
                 }
             }
 
-            #[cfg(not(all(support_priority, not(feature = "test_no_global_lazy_hint"))))]
+            #[cfg(not(all(support_priority, feature = "macros", not(feature = "test_no_global_lazy_hint"))))]
             Self::__do_init(this);
         }
+        /// Ensure the value is initialized and return a reference to it, or return the payload
+        /// of the panic of the initializer.
+        ///
+        /// If the initializer panics, the lazy is poisoned: later accesses panic and later calls
+        /// to this function return the payload of the panic reporting the poisoning, a message
+        /// string, not the payload of the panic of the initializer.
+        pub fn force_with_unwind(this: &Self) -> Result<&T, Box<dyn Any + Send>>
+        where
+            F: FnOnce() -> T,
+        {
+            std::panic::catch_unwind(AssertUnwindSafe(|| Self::__do_init(this)))?;
+            Ok(unsafe { &*Self::as_mut_ptr(this) })
+        }
+
+        /// Return true if the value has been initialized.
+        #[inline(always)]
+        pub fn is_initialized(this: &Self) -> bool {
+            this.pre_initialized || this.initer.state().done()
+        }
+
+        /// Return a reference to the value without checking that it is initialized.
+        ///
+        /// In debug mode, this function panics if the value is not initialized.
+        ///
+        /// # Safety
+        ///
+        /// The value must be initialized and not dropped.
+        #[inline(always)]
+        pub unsafe fn get_unchecked(this: &Self) -> &T {
+            &*Self::as_mut_ptr(this)
+        }
+
+        /// Replace the value by `value` until the returned guard is dropped, then restore the
+        /// previous value.
+        ///
+        /// This is intended to inject values in lazy statics from tests. The lazy is initialized
+        /// before its value is replaced. The overriding value is dropped when the guard is
+        /// dropped.
+        ///
+        /// # Safety
+        ///
+        /// The lazy shall not be dropped while the guard is alive. References to the value
+        /// obtained before the call shall not be used while the guard is alive, and references
+        /// obtained while the guard is alive shall not be used after it is dropped. No other
+        /// thread shall access the value during the call and during the drop of the guard.
+        #[cfg(feature = "test_override")]
+        pub unsafe fn scoped_override(this: &Self, value: T) -> OverrideGuard<'_, T>
+        where
+            F: FnOnce() -> T,
+        {
+            Self::ensure_init(this);
+            OverrideGuard::new(Self::as_mut_ptr(this), value)
+        }
+
+        /// Return the information recorded about this static.
+        ///
+        /// Always `None` when not in debug mode.
+        #[inline]
+        pub fn info(_: &Self) -> Option<&StaticInfo> {
+            None
+        }
+
+        /// Return the backtrace of the access that triggered the initialization.
+        ///
+        /// The backtrace is only recorded in debug mode and is captured with
+        /// `Backtrace::capture`, so it is only non empty if backtraces are enabled through
+        /// the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables.
+        #[inline]
+        pub fn init_backtrace(_: &Self) -> Option<&Backtrace> {
+            None
+        }
+
+        #[inline]
+        pub(crate) fn init_rank(_: &Self) -> Option<usize> {
+            None
+        }
+
         /// Drop the contained value
         ///
         /// # Safety
@@ -386,6 +883,10 @@ mod lazy_impl {
         pub unsafe fn drop(this: &Self) {
             Self::as_mut_ptr(this).drop_in_place()
         }
+
+        #[doc(hidden)]
+        #[inline(always)]
+        pub fn __drop_registered(_: &Self) {}
     }
 
     unsafe impl<F, T: Send + Sync> Send for Lazy<T, F> {}
@@ -437,6 +938,14 @@ mod lazy_impl {
             Self(Lazy::new(f))
         }
 
+        /// Create a lazy that is already initialized with `v`.
+        ///
+        /// This allows statics whose value is sometimes known at compile time to have the
+        /// same type as lazy statics. The initializer is never called.
+        pub const fn from_value(v: T) -> Self {
+            Self(Lazy::from_value(v))
+        }
+
         /// Return a pointer to the value.
         ///
         /// The value may be in an uninitialized state.
@@ -468,6 +977,84 @@ mod lazy_impl {
         {
             Lazy::ensure_init(&this.0)
         }
+
+        /// Ensure the value is initialized and return a reference to it, or return the payload
+        /// of the panic of the initializer.
+        ///
+        /// See [Lazy::force_with_unwind].
+        pub fn force_with_unwind(this: &Self) -> Result<&T, Box<dyn Any + Send>>
+        where
+            F: FnOnce() -> T,
+        {
+            Lazy::force_with_unwind(&this.0)
+        }
+
+        /// Return true if the value has been initialized.
+        #[inline(always)]
+        pub fn is_initialized(this: &Self) -> bool {
+            Lazy::is_initialized(&this.0)
+        }
+
+        /// Return a reference to the value without checking that it is initialized.
+        ///
+        /// In debug mode, this function panics if the value is not initialized.
+        ///
+        /// # Safety
+        ///
+        /// The value must be initialized and not dropped.
+        #[inline(always)]
+        pub unsafe fn get_unchecked(this: &Self) -> &T {
+            Lazy::get_unchecked(&this.0)
+        }
+
+        /// Replace the value by `value` until the returned guard is dropped.
+        ///
+        /// See [Lazy::scoped_override].
+        ///
+        /// # Safety
+        ///
+        /// See [Lazy::scoped_override].
+        #[cfg(feature = "test_override")]
+        pub unsafe fn scoped_override(this: &Self, value: T) -> OverrideGuard<'_, T>
+        where
+            F: FnOnce() -> T,
+        {
+            Lazy::scoped_override(&this.0, value)
+        }
+
+        /// Return the information recorded about this static.
+        #[inline]
+        pub fn info(this: &Self) -> Option<&StaticInfo> {
+            Lazy::info(&this.0)
+        }
+
+        /// Return the backtrace of the access that triggered the initialization.
+        ///
+        /// See [Lazy::init_backtrace].
+        #[inline]
+        pub fn init_backtrace(this: &Self) -> Option<&Backtrace> {
+            Lazy::init_backtrace(&this.0)
+        }
+
+        #[inline]
+        pub(crate) fn init_rank(this: &Self) -> Option<usize> {
+            Lazy::init_rank(&this.0)
+        }
+
+        /// Drop the contained value
+        ///
+        /// # Safety
+        ///
+        /// The value should not be accessed any more.
+        pub unsafe fn drop(this: &Self) {
+            Lazy::drop(&this.0)
+        }
+
+        #[doc(hidden)]
+        #[inline(always)]
+        pub fn __drop_registered(this: &Self) {
+            Lazy::__drop_registered(&this.0)
+        }
     }
 
     impl<T, F> Deref for ConstLazy<T, F>
@@ -0,0 +1,82 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::MaybeUninit;
+
+/// The memory slot holding the value of *dynamic statics* and *lazy statics*.
+///
+/// Its invariants are:
+///
+/// - it has the same size and alignment as `T`, and the value is at offset 0;
+/// - its const-initialized state is either uninitialized memory ([StaticStorage::uninit])
+///   or the value itself ([StaticStorage::new]), so no bit pattern is reserved in the slot;
+/// - it never drops its content, the owner of the storage tracks whether it is initialized.
+///
+/// [StaticStorage::as_ptr] gives access to the slot from a shared reference, which can be used to
+/// write initialization code that does not fit the `dynamic` attribute (for example a copy from
+/// flash memory with `read_volatile`).
+///
+/// The slot is an `UnsafeCell<MaybeUninit<T>>` rather than a plain `MaybeUninit<T>`: the value
+/// is written through a shared reference, and without `UnsafeCell` a non mutable static may be
+/// placed in read only memory and such a write is undefined behavior.
+///
+/// As [StaticStorage::write] and [StaticStorage::drop_in_place] move a value in or drop it
+/// through a shared reference, the storage is `Sync` only if `T` is `Send` and `Sync`.
+#[repr(transparent)]
+pub struct StaticStorage<T>(UnsafeCell<MaybeUninit<T>>);
+
+impl<T> StaticStorage<T> {
+    /// Create an uninitialized storage.
+    #[inline(always)]
+    pub const fn uninit() -> Self {
+        Self(UnsafeCell::new(MaybeUninit::uninit()))
+    }
+
+    /// Create a storage holding `v`.
+    #[inline(always)]
+    pub const fn new(v: T) -> Self {
+        Self(UnsafeCell::new(MaybeUninit::new(v)))
+    }
+
+    /// Return a pointer to the slot.
+    ///
+    /// The value may be in an uninitialized state.
+    #[inline(always)]
+    pub const fn as_ptr(&self) -> *mut T {
+        self.0.get() as *mut T
+    }
+
+    /// Write `v` in the slot without dropping the previous content.
+    ///
+    /// # Safety
+    ///
+    /// No reference to the value shall be alive.
+    #[inline(always)]
+    pub unsafe fn write(&self, v: T) {
+        self.as_ptr().write(v)
+    }
+
+    /// Drop the value in place.
+    ///
+    /// # Safety
+    ///
+    /// The slot shall be initialized and the value shall not be accessed any more.
+    #[inline(always)]
+    pub unsafe fn drop_in_place(&self) {
+        self.as_ptr().drop_in_place()
+    }
+}
+
+unsafe impl<T: Send + Sync> Sync for StaticStorage<T> {}
+
+impl<T> fmt::Debug for StaticStorage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StaticStorage { .. }")
+    }
+}
@@ -1,8 +1,44 @@
-#[cfg(debug_mode)]
-use super::StaticInfo;
+use super::{StaticInfo, StaticStorage};
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ptr;
 
 pub use lazy_impl::{Lazy, ConstLazy};
 
+/// Error returned by `try_with` when the thread local lazy has
+/// already been dropped at thread exit.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AccessError(());
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("thread local lazy already dropped")
+    }
+}
+
+#[cfg(any(feature = "lazy", feature = "thread_local_drop"))]
+impl std::error::Error for AccessError {}
+
+// Restore the overridden value, even if the closure panics.
+struct Restore<T> {
+    ptr:  *mut T,
+    prev: ManuallyDrop<T>,
+}
+
+impl<T> Drop for Restore<T> {
+    fn drop(&mut self) {
+        unsafe { drop(ptr::replace(self.ptr, ManuallyDrop::take(&mut self.prev))) }
+    }
+}
+
+unsafe fn with_override_at<T, R>(ptr: *mut T, value: T, f: impl FnOnce() -> R) -> R {
+    let _restore = Restore {
+        ptr,
+        prev: ManuallyDrop::new(ptr::replace(ptr, value)),
+    };
+    f()
+}
+
 #[cfg(not(debug_mode))]
 mod lazy_impl {
 
@@ -10,15 +46,16 @@ mod lazy_impl {
     use likely_stable::if_unlikely;
 
     use core::cell::Cell;
-    use core::cell::UnsafeCell;
     use core::fmt;
-    use core::mem::MaybeUninit;
     use core::ops::{Deref, DerefMut};
 
+    use super::{AccessError, StaticInfo, StaticStorage};
+
     /// The type of thread local lazy.
     pub struct Lazy<T, F = fn() -> T> {
-        value:    UnsafeCell<MaybeUninit<T>>,
+        value:    StaticStorage<T>,
         init_exp: Cell<Option<F>>,
+        dropped:  Cell<bool>,
     }
 
     /// The type of const thread local lazy that will be dropped.
@@ -32,8 +69,9 @@ mod lazy_impl {
         /// Initialize a lazy with a builder as argument.
         pub const fn new(f: F) -> Self {
             Self {
-                value:    UnsafeCell::new(MaybeUninit::uninit()),
+                value:    StaticStorage::uninit(),
                 init_exp: Cell::new(Some(f)),
+                dropped:  Cell::new(false),
             }
         }
 
@@ -42,7 +80,15 @@ mod lazy_impl {
         /// The value may be in an uninitialized state.
         #[inline(always)]
         pub const fn as_mut_ptr(this: &Self) -> *mut T {
-            this.value.get() as *mut T
+            this.value.as_ptr()
+        }
+
+        /// Return the information recorded about this static.
+        ///
+        /// Always `None` when not in debug mode.
+        #[inline]
+        pub fn info(_: &Self) -> Option<&StaticInfo> {
+            None
         }
 
         /// Ensure the value is initialized
@@ -72,22 +118,78 @@ mod lazy_impl {
         {
             #[cfg(not(feature = "likely"))]
             if let Some(f) = this.init_exp.take() {
-                unsafe { (*this.value.get()).as_mut_ptr().write(f()) };
+                unsafe { this.value.write(f()) };
             }
             #[cfg(feature = "likely")]
             if_unlikely!{let Some(_f) = this.init_exp.take() => {
-                unsafe { (*this.value.get()).as_mut_ptr().write(_f()) };
+                unsafe { this.value.write(_f()) };
             }}
         }
 
+        /// Call `f` with a reference to the value, initializing it if needed.
+        ///
+        /// Contrary to a dereference, this function does not access the value if it
+        /// has already been dropped at thread exit but returns an error.
+        #[inline]
+        pub fn try_with<R>(this: &Self, f: impl FnOnce(&T) -> R) -> Result<R, AccessError>
+        where
+            F: FnOnce() -> T,
+        {
+            Self::ensure_init(this);
+            if this.dropped.get() {
+                return Err(AccessError(()));
+            }
+            Ok(f(unsafe { &*Self::as_mut_ptr(this) }))
+        }
+
+        /// Return a reference to the value without checking that it is initialized.
+        ///
+        /// In debug mode, this function panics if the value is not initialized.
+        ///
+        /// # Safety
+        ///
+        /// The value must be initialized and not dropped.
+        #[inline(always)]
+        pub unsafe fn get_unchecked(this: &Self) -> &T {
+            &*Self::as_mut_ptr(this)
+        }
+
+        /// Run `f` with `value` as the value of the thread local, then restore the previous
+        /// value, even if `f` panics.
+        ///
+        /// The overriding value is visible to any code run by `f` on the current thread. It is
+        /// dropped when the previous value is restored.
+        ///
+        /// # Safety
+        ///
+        /// The thread local shall not be dropped. References to the value obtained before the
+        /// call shall not be used during the call, and references obtained during the call shall
+        /// not be used after it.
+        pub unsafe fn with_override<R>(this: &Self, value: T, f: impl FnOnce() -> R) -> R
+        where
+            F: FnOnce() -> T,
+        {
+            Self::ensure_init(this);
+            super::with_override_at(Self::as_mut_ptr(this), value, f)
+        }
+
         /// Drop the contained value
         ///
         /// # Safety
         ///
         /// The value should not be accessed any more.
         pub unsafe fn drop(this: &Self) {
+            this.dropped.set(true);
             Self::as_mut_ptr(this).drop_in_place()
         }
+
+        /// Mark the value as dropped without dropping it, the value is leaked.
+        ///
+        /// Used by the dynamic macro when the drop of the value can not be registered.
+        #[doc(hidden)]
+        pub fn __mark_dropped(this: &Self) {
+            this.dropped.set(true)
+        }
     }
 
     impl<T, F> Deref for Lazy<T, F>
@@ -143,6 +245,12 @@ mod lazy_impl {
         pub const fn as_mut_ptr(this: &Self) -> *mut T {
             Lazy::as_mut_ptr(&this.0)
         }
+
+        /// Return the information recorded about this static.
+        #[inline]
+        pub fn info(this: &Self) -> Option<&StaticInfo> {
+            Lazy::info(&this.0)
+        }
         /// Ensure the value is initialized without optimization check
         ///
         /// This is intended to be used at program start up by
@@ -167,6 +275,56 @@ mod lazy_impl {
         {
             Self::__do_init(this);
         }
+
+        /// Call `f` with a reference to the value, initializing it if needed.
+        ///
+        /// Returns an error if the value has already been dropped at thread exit.
+        #[inline]
+        pub fn try_with<R>(this: &Self, f: impl FnOnce(&T) -> R) -> Result<R, AccessError>
+        where
+            F: FnOnce() -> T,
+        {
+            Lazy::try_with(&this.0, f)
+        }
+
+        /// Return a reference to the value without checking that it is initialized.
+        ///
+        /// In debug mode, this function panics if the value is not initialized.
+        ///
+        /// # Safety
+        ///
+        /// The value must be initialized and not dropped.
+        #[inline(always)]
+        pub unsafe fn get_unchecked(this: &Self) -> &T {
+            Lazy::get_unchecked(&this.0)
+        }
+
+        /// Run `f` with `value` as the value of the thread local, then restore the previous
+        /// value, even if `f` panics.
+        ///
+        /// # Safety
+        ///
+        /// See [Lazy::with_override].
+        pub unsafe fn with_override<R>(this: &Self, value: T, f: impl FnOnce() -> R) -> R
+        where
+            F: FnOnce() -> T,
+        {
+            Lazy::with_override(&this.0, value, f)
+        }
+
+        /// Drop the contained value
+        ///
+        /// # Safety
+        ///
+        /// The value should not be accessed any more.
+        pub unsafe fn drop(this: &Self) {
+            Lazy::drop(&this.0)
+        }
+
+        #[doc(hidden)]
+        pub fn __mark_dropped(this: &Self) {
+            Lazy::__mark_dropped(&this.0)
+        }
     }
 
     impl<T, F> Deref for ConstLazy<T, F>
@@ -188,11 +346,9 @@ mod lazy_impl {
 mod lazy_impl {
     #[cfg(feature = "likely")]
     use likely_stable::if_unlikely;
-    use super::StaticInfo;
+    use super::{AccessError, StaticInfo, StaticStorage};
     use core::cell::Cell;
-    use core::cell::UnsafeCell;
     use core::fmt;
-    use core::mem::MaybeUninit;
     use core::ops::{Deref, DerefMut};
 
     #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -205,7 +361,7 @@ mod lazy_impl {
 
     /// The type of thread local lazy.
     pub struct Lazy<T, F = fn() -> T> {
-        value:    UnsafeCell<MaybeUninit<T>>,
+        value:    StaticStorage<T>,
         init_exp: Cell<Option<F>>,
         status:   Cell<Status>,
         info:     Option<StaticInfo>,
@@ -225,7 +381,7 @@ mod lazy_impl {
         /// by the dynamic macro.
         pub const fn new(f: F, info: StaticInfo) -> Self {
             Self {
-                value:    UnsafeCell::new(MaybeUninit::uninit()),
+                value:    StaticStorage::uninit(),
                 init_exp: Cell::new(Some(f)),
                 status:   Cell::new(Status::NotInitialized),
                 info:     Some(info),
@@ -237,7 +393,13 @@ mod lazy_impl {
         /// The value may be in an uninitialized state.
         #[inline(always)]
         pub const fn as_mut_ptr(this: &Self) -> *mut T {
-            this.value.get() as *mut T
+            this.value.as_ptr()
+        }
+
+        /// Return the information recorded about this static.
+        #[inline]
+        pub fn info(this: &Self) -> Option<&StaticInfo> {
+            this.info.as_ref()
         }
 
         /// Ensure the value is initialized
@@ -270,8 +432,11 @@ mod lazy_impl {
                 match this.status.get() {
                     Status::NotInitialized => {
                         this.status.set(Status::Initializing);
-                        unsafe { (*this.value.get()).as_mut_ptr().write(f()) };
-                        this.status.set(Status::Initialized);
+                        unsafe { this.value.write(f()) };
+                        // the initializer may have marked the value as dropped
+                        if this.status.get() == Status::Initializing {
+                            this.status.set(Status::Initialized);
+                        }
                     }
                     _ => panic!("Unexpected"),
                 }
@@ -282,14 +447,68 @@ mod lazy_impl {
                 match this.status.get() {
                     Status::NotInitialized => {
                         this.status.set(Status::Initializing);
-                        unsafe { (*this.value.get()).as_mut_ptr().write(_f()) };
-                        this.status.set(Status::Initialized);
+                        unsafe { this.value.write(_f()) };
+                        // the initializer may have marked the value as dropped
+                        if this.status.get() == Status::Initializing {
+                            this.status.set(Status::Initialized);
+                        }
                     }
                     _ => panic!("Unexpected"),
                 }
             }}
 
         }
+
+        /// Call `f` with a reference to the value, initializing it if needed.
+        ///
+        /// Contrary to a dereference, this function does not panic if the value
+        /// has already been dropped at thread exit but returns an error.
+        #[inline]
+        pub fn try_with<R>(this: &Self, f: impl FnOnce(&T) -> R) -> Result<R, AccessError>
+        where
+            F: FnOnce() -> T,
+        {
+            Self::ensure_init(this);
+            if this.status.get() == Status::Droped {
+                return Err(AccessError(()));
+            }
+            check_status(this.status.get(), &this.info);
+            Ok(f(unsafe { &*Self::as_mut_ptr(this) }))
+        }
+
+        /// Return a reference to the value without checking that it is initialized.
+        ///
+        /// In debug mode, this function panics if the value is not initialized.
+        ///
+        /// # Safety
+        ///
+        /// The value must be initialized and not dropped.
+        #[inline(always)]
+        pub unsafe fn get_unchecked(this: &Self) -> &T {
+            check_status(this.status.get(), &this.info);
+            &*Self::as_mut_ptr(this)
+        }
+
+        /// Run `f` with `value` as the value of the thread local, then restore the previous
+        /// value, even if `f` panics.
+        ///
+        /// The overriding value is visible to any code run by `f` on the current thread. It is
+        /// dropped when the previous value is restored.
+        ///
+        /// # Safety
+        ///
+        /// The thread local shall not be dropped. References to the value obtained before the
+        /// call shall not be used during the call, and references obtained during the call shall
+        /// not be used after it.
+        pub unsafe fn with_override<R>(this: &Self, value: T, f: impl FnOnce() -> R) -> R
+        where
+            F: FnOnce() -> T,
+        {
+            Self::ensure_init(this);
+            check_status(this.status.get(), &this.info);
+            super::with_override_at(Self::as_mut_ptr(this), value, f)
+        }
+
         /// Drop the contained value
         ///
         /// # Safety
@@ -300,6 +519,14 @@ mod lazy_impl {
             Self::as_mut_ptr(this).drop_in_place();
             this.status.set(Status::Droped);
         }
+
+        /// Mark the value as dropped without dropping it, the value is leaked.
+        ///
+        /// Used by the dynamic macro when the drop of the value can not be registered.
+        #[doc(hidden)]
+        pub fn __mark_dropped(this: &Self) {
+            this.status.set(Status::Droped)
+        }
     }
     fn check_status(st: Status, info: &Option<StaticInfo>) {
         match st {
@@ -312,9 +539,16 @@ mod lazy_impl {
             }
             Status::Droped => {
                 if let Some(info) = info {
-                    core::panic!("Attempt to access {:#?} after it has been dropped.", info);
+                    core::panic!(
+                        "Attempt to access {:#?} after it has been dropped at thread exit. It may \
+                         be accessed by the drop of a thread_local that is dropped after it.",
+                        info
+                    );
                 } else {
-                    core::panic!("Attempt to access a thread_local after it has been dropped.");
+                    core::panic!(
+                        "Attempt to access a thread_local after it has been dropped at thread \
+                         exit."
+                    );
                 }
             }
             Status::NotInitialized => {
@@ -393,6 +627,12 @@ mod lazy_impl {
         pub const fn as_mut_ptr(this: &Self) -> *mut T {
             Lazy::as_mut_ptr(&this.0)
         }
+
+        /// Return the information recorded about this static.
+        #[inline]
+        pub fn info(this: &Self) -> Option<&StaticInfo> {
+            Lazy::info(&this.0)
+        }
         /// Ensure the value is initialized without optimization check
         ///
         /// This is intended to be used at program start up by
@@ -417,6 +657,56 @@ mod lazy_impl {
         {
             Self::__do_init(this);
         }
+
+        /// Call `f` with a reference to the value, initializing it if needed.
+        ///
+        /// Returns an error if the value has already been dropped at thread exit.
+        #[inline]
+        pub fn try_with<R>(this: &Self, f: impl FnOnce(&T) -> R) -> Result<R, AccessError>
+        where
+            F: FnOnce() -> T,
+        {
+            Lazy::try_with(&this.0, f)
+        }
+
+        /// Return a reference to the value without checking that it is initialized.
+        ///
+        /// In debug mode, this function panics if the value is not initialized.
+        ///
+        /// # Safety
+        ///
+        /// The value must be initialized and not dropped.
+        #[inline(always)]
+        pub unsafe fn get_unchecked(this: &Self) -> &T {
+            Lazy::get_unchecked(&this.0)
+        }
+
+        /// Run `f` with `value` as the value of the thread local, then restore the previous
+        /// value, even if `f` panics.
+        ///
+        /// # Safety
+        ///
+        /// See [Lazy::with_override].
+        pub unsafe fn with_override<R>(this: &Self, value: T, f: impl FnOnce() -> R) -> R
+        where
+            F: FnOnce() -> T,
+        {
+            Lazy::with_override(&this.0, value, f)
+        }
+
+        /// Drop the contained value
+        ///
+        /// # Safety
+        ///
+        /// The value should not be accessed any more.
+        pub unsafe fn drop(this: &Self) {
+            Lazy::drop(&this.0)
+        }
+
+        #[doc(hidden)]
+        pub fn __mark_dropped(this: &Self) {
+            Lazy::__mark_dropped(&this.0)
+        }
     }
 
     impl<T, F> Deref for ConstLazy<T, F>
@@ -426,10 +716,7 @@ mod lazy_impl {
         type Target = T;
         #[inline(always)]
         fn deref(&self) -> &T {
-            unsafe {
-                ConstLazy::ensure_init(self);
-                &*ConstLazy::as_mut_ptr(self)
-            }
+            &self.0
         }
     }
 }
@@ -437,7 +724,9 @@ mod lazy_impl {
 #[cfg(feature = "thread_local_drop")]
 mod lazy_drop {
     use core::cell::UnsafeCell;
-    struct DestructorRegister(UnsafeCell<Option<Vec<fn()>>>);
+    type Destructors = Option<Vec<fn()>>;
+
+    struct DestructorRegister(UnsafeCell<Destructors>);
 
     impl Drop for DestructorRegister {
         fn drop(&mut self) {
@@ -455,22 +744,31 @@ mod lazy_drop {
         static DESTRUCTORS: DestructorRegister = DestructorRegister(UnsafeCell::new(None));
     }
 
+    /// Register `f` to be run at the exit of the current thread.
+    ///
+    /// The registration relies on the standard library thread_local destructors, so `f`
+    /// is also run at the exit of threads created by foreign code.
+    ///
+    /// Returns false if the thread local destructors of the current thread are already running,
+    /// in which case `f` will not be run.
     #[doc(hidden)]
     #[inline(always)]
-    unsafe fn ensure_init() {
-        DESTRUCTORS.with(|d| {
-            if (*d.0.get()).is_none() {
-                *d.0.get() = Some(vec![])
-            }
-        })
+    pub unsafe fn __push_tls_destructor(f: fn()) -> bool {
+        DESTRUCTORS
+            .try_with(|d| (*d.0.get()).get_or_insert_with(Vec::new).push(f))
+            .is_ok()
     }
 
+    /// Report a thread local lazy whose drop could not be registered.
     #[doc(hidden)]
-    #[inline(always)]
-    pub unsafe fn __push_tls_destructor(f: fn()) {
-        ensure_init();
-        DESTRUCTORS.with(|d| (*d.0.get()).as_mut().unwrap().push(f));
+    #[cold]
+    pub fn __leak_late_tls(name: &str) {
+        std::eprintln!(
+            "static_init: the thread local `{}` was initialized while its thread exits, \
+             its value is leaked.",
+            name
+        )
     }
 }
 #[cfg(feature = "thread_local_drop")]
-pub use lazy_drop::__push_tls_destructor;
+pub use lazy_drop::{__leak_late_tls, __push_tls_destructor};
@@ -1,6 +1,11 @@
 use cfg_aliases::cfg_aliases;
 
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(elf)");
+    println!("cargo:rustc-check-cfg=cfg(coff)");
+    println!("cargo:rustc-check-cfg=cfg(mach_o)");
+    println!("cargo:rustc-check-cfg=cfg(debug_mode)");
+
     cfg_aliases! {
 
         elf: { any(
@@ -5,9 +5,10 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-///! Macros for static_init crate.
+//! Macros for static_init crate.
 extern crate proc_macro;
 extern crate syn;
+use syn::ext::IdentExt;
 use syn::spanned::Spanned;
 use syn::*;
 
@@ -48,6 +49,10 @@ macro_rules! ok_or_return {
 ///
 /// An abscence of priority is equivalent to a priority of 0.
 ///
+/// The priority can also be `first` or `last`. `#[constructor(first)]` is run before the other
+/// constructors but after the initializations of the C, C++ and Rust runtimes (priority 65434 on
+/// ELF plateforms, 65535 on windows). `#[constructor(last)]` has priority 0.
+///
 /// # Safety
 ///
 /// Any access to [macro@dynamic] statics with an equal or lower
@@ -76,6 +81,11 @@ macro_rules! ok_or_return {
 /// objects are initialized as constructors with no priorities. On ELF plateform, libstdc++
 /// resources are initialized with priority 65535-100.
 ///
+/// On ELF plateforms, the priorities 65535-99 and 65535-100 are rejected because they
+/// would be undeterminately sequenced with those runtime initializations. The module
+/// `static_init::priority` provides constants for the priorities that are sequenced before or
+/// after them.
+///
 /// # Constructor signature
 ///
 /// Constructor function should have type `extern "C" fn() -> ()`.
@@ -87,19 +97,62 @@ macro_rules! ok_or_return {
 /// sequence, `argv` and `env` both refer to null terminated contiguous sequence of pointer
 /// to c-string (c-strings are null terminated sequence of u8).
 /// Cf "glibc source"/csu/elf-init.c, and System V ABI.
+///
+/// A constructor can also return a `Result<(), E>` where `E: Debug`. It then does not need to be
+/// `extern "C"`. If it returns an error, the error is passed to the handler set with
+/// `static_init::set_constructor_error_handler`, which by default prints it and aborts the
+/// program.
+///
+/// ```ignore
+/// #[constructor]
+/// fn check_config() -> Result<(), String> {
+///     Err("missing configuration".to_string())
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn constructor(args: TokenStream, input: TokenStream) -> TokenStream {
-    let priority = ok_or_return!(parse_priority(args));
-
-    let section = ok_or_return!(init_section(priority));
+    let priority = ok_or_return!(parse_priority(
+        args,
+        Priority::EarliestSafe,
+        Priority::Value(0)
+    ));
+
+    let init_priority = |priority| if cfg!(debug_mode) { Some(priority) } else { None };
+
+    let (check, slots) = match priority {
+        Priority::Value(priority) => (
+            check_init_priority(priority, Span::call_site()),
+            vec![Slot {
+                cfg:           None,
+                section:       ok_or_return!(init_section(priority)),
+                init_priority: init_priority(priority),
+            }],
+        ),
+        Priority::EarliestSafe => (
+            quote!(),
+            FORMATS
+                .iter()
+                .map(|f| {
+                    let priority = f.earliest_safe_priority();
+                    Slot {
+                        cfg:           Some(f.cfg()),
+                        section:       f.init_section(priority).unwrap(),
+                        init_priority: init_priority(priority),
+                    }
+                })
+                .collect(),
+        ),
+    };
 
     let func: ItemFn = parse_macro_input!(input);
 
-    let func_ptr_name = format!("__static_init_constructor_{}", func.sig.ident);
+    let func_ptr_name = format!("__static_init_constructor_{}", func.sig.ident.unraw());
 
     let func_type = get_init_func_sig(&func.sig);
 
-    gen_ctor_dtor(func, &section, &func_ptr_name, func_type).into()
+    let ctor = gen_ctor_dtor(func, &slots, &func_ptr_name, func_type);
+
+    quote!(#check #ctor).into()
 }
 
 fn get_init_func_sig(sig: &Signature) -> TypeBareFn {
@@ -120,41 +173,112 @@ fn const_dtor_no_support() -> TokenStream {
     .into()
 }
 
-fn init_section(priority: u16) -> Result<String, TokenStream> {
-    if cfg!(elf) {
-        Ok(format!(".init_array.{:05}", 65535 - priority))
-    } else if cfg!(mach_o) {
-        if priority != 0 {
-            Err(quote!(compile_error!(
-                "Constructor priority other than 0 not supported on this plateform."
-            ))
-            .into())
+/// Object file formats that support constructors and destructors.
+#[derive(Clone, Copy)]
+enum Format {
+    Elf,
+    MachO,
+    Coff,
+}
+
+const FORMATS: [Format; 3] = [Format::Elf, Format::MachO, Format::Coff];
+
+impl Format {
+    /// The format of the target when it is the host.
+    fn host() -> Option<Self> {
+        if cfg!(elf) {
+            Some(Format::Elf)
+        } else if cfg!(mach_o) {
+            Some(Format::MachO)
+        } else if cfg!(coff) {
+            Some(Format::Coff)
         } else {
-            Ok("__DATA,__mod_init_func".to_string())
+            None
+        }
+    }
+
+    /// The cfg alias of static_init for this format, as a predicate evaluated in the crate that
+    /// uses the macro: `cfg!` in this crate would describe the host when cross compiling.
+    fn cfg(self) -> TokenStream2 {
+        match self {
+            // keep in sync with the elf alias in the build scripts
+            Format::Elf => quote!(any(
+                target_os = "linux",
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "dragonfly",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "solaris",
+                target_os = "illumos",
+                target_os = "emscripten",
+                target_os = "haiku",
+                target_os = "l4re",
+                target_os = "fuchsia",
+                target_os = "redox",
+                target_os = "vxworks"
+            )),
+            Format::MachO => quote!(any(target_os = "macos", target_os = "ios")),
+            Format::Coff => quote!(target_os = "windows"),
+        }
+    }
+
+    /// Priority of the earliest slot that is sequenced after the initializations of the
+    /// runtime (or before its finalization).
+    fn earliest_safe_priority(self) -> u16 {
+        match self {
+            Format::Elf => LIBSTDCXX_RESOURCES - 1,
+            Format::MachO => 0,
+            Format::Coff => 65535,
+        }
+    }
+
+    fn init_section(self, priority: u16) -> Result<String, TokenStream> {
+        match self {
+            Format::Elf => Ok(format!(".init_array.{:05}", 65535 - priority)),
+            Format::MachO => {
+                if priority != 0 {
+                    Err(quote!(compile_error!(
+                        "Constructor priority other than 0 not supported on this plateform."
+                    ))
+                    .into())
+                } else {
+                    Ok("__DATA,__mod_init_func".to_string())
+                }
+            }
+            Format::Coff => Ok(format!(".CRT$XCTZ{:05}", 65535 - priority)),
+        }
+    }
+
+    fn fini_section(self, priority: u16) -> Result<String, TokenStream> {
+        match self {
+            Format::Elf => Ok(format!(".fini_array.{:05}", 65535 - priority)),
+            Format::MachO => {
+                if priority != 0 {
+                    Err(quote!(compile_error!(
+                        "Constructor priority not supported on this plateform."
+                    ))
+                    .into())
+                } else {
+                    Ok("__DATA,__mod_term_func".to_string())
+                }
+            }
+            Format::Coff => Ok(format!(".CRT$XPTZ{:05}", 65535 - priority)),
         }
-    } else if cfg!(coff) {
-        Ok(format!(".CRT$XCTZ{:05}", 65535 - priority))
-    } else {
-        Err(const_dtor_no_support())
+    }
+}
+
+fn init_section(priority: u16) -> Result<String, TokenStream> {
+    match Format::host() {
+        Some(format) => format.init_section(priority),
+        None => Err(const_dtor_no_support()),
     }
 }
 
 fn fini_section(priority: u16) -> Result<String, TokenStream> {
-    if cfg!(elf) {
-        Ok(format!(".fini_array.{:05}", 65535 - priority))
-    } else if cfg!(mach_o) {
-        if priority != 0 {
-            Err(quote!(compile_error!(
-                "Constructor priority not supported on this plateform."
-            ))
-            .into())
-        } else {
-            Ok("__DATA,__mod_term_func".to_string())
-        }
-    } else if cfg!(coff) {
-        Ok(format!(".CRT$XPTZ{:05}", 65535 - priority))
-    } else {
-        Err(const_dtor_no_support())
+    match Format::host() {
+        Some(format) => format.fini_section(priority),
+        None => Err(const_dtor_no_support()),
     }
 }
 
@@ -176,6 +300,10 @@ fn fini_section(priority: u16) -> Result<String, TokenStream> {
 ///
 /// An abscence of priority is equivalent to a priority of 0.
 ///
+/// The priority can also be `first` or `last`. `#[destructor(first)]` has priority 0.
+/// `#[destructor(last)]` is run after the other destructors but before the finalizations of the
+/// C, C++ and Rust runtimes (priority 65434 on ELF plateforms, 65535 on windows).
+///
 /// ```ignore
 /// #[destructor(1)]
 /// unsafe extern "C" fn first () {
@@ -193,18 +321,43 @@ fn fini_section(priority: u16) -> Result<String, TokenStream> {
 /// Destructor function should have type `unsafe extern "C" fn() -> ()`.
 #[proc_macro_attribute]
 pub fn destructor(args: TokenStream, input: TokenStream) -> TokenStream {
-    let priority = ok_or_return!(parse_priority(args));
-
-    let section = ok_or_return!(fini_section(priority));
+    let priority = ok_or_return!(parse_priority(
+        args,
+        Priority::Value(0),
+        Priority::EarliestSafe
+    ));
+
+    let slots = match priority {
+        Priority::Value(priority) => vec![Slot {
+            cfg:           None,
+            section:       ok_or_return!(fini_section(priority)),
+            init_priority: None,
+        }],
+        Priority::EarliestSafe => FORMATS
+            .iter()
+            .map(|f| Slot {
+                cfg:           Some(f.cfg()),
+                section:       f.fini_section(f.earliest_safe_priority()).unwrap(),
+                init_priority: None,
+            })
+            .collect(),
+    };
 
     let func: ItemFn = parse_macro_input!(input);
 
-    let func_ptr_name = format!("__static_init_destructor_{}", func.sig.ident);
+    if let ReturnType::Type(..) = func.sig.output {
+        return quote_spanned!(func.sig.output.span()=>compile_error!(
+            "destructors can not return a value"
+        ))
+        .into();
+    }
+
+    let func_ptr_name = format!("__static_init_destructor_{}", func.sig.ident.unraw());
 
     let sp = func.sig.span();
     let func_type = parse2(quote_spanned!(sp.span()=>extern "C" fn())).unwrap();
 
-    gen_ctor_dtor(func, &section, &func_ptr_name, func_type).into()
+    gen_ctor_dtor(func, &slots, &func_ptr_name, func_type).into()
 }
 
 /// Statics initialized with non const functions.
@@ -223,6 +376,7 @@ pub fn destructor(args: TokenStream, input: TokenStream) -> TokenStream {
 /// statics are optimized versions of [std::lazy::SyncLazy]. After program initialization phase,
 /// those statics are guaranteed to be initialized and access to them will be as fast as any access
 /// to a regular static. On other plateforms, those statics are equivalent to [std::lazy::SyncLazy].
+/// The constant `static_init::caps::HAS_LESSER_LAZY` tells which case applies to the target.
 ///
 /// ```ignore
 /// struct A(i32);
@@ -275,6 +429,54 @@ pub fn destructor(args: TokenStream, input: TokenStream) -> TokenStream {
 /// Internaly the procedural macro change V1 to a mutable statics and wrap it in a type
 /// that does not implement `DerefMut`.
 ///
+/// With `[dynamic(lazy,drop_in_debug_only)]` the static is declared as with `drop`, but its drop is
+/// only registered when debug assertions are enabled in the crate that declares it. Release
+/// builds skip the teardown, while debug builds still drop the static, for example to keep leak
+/// checkers quiet.
+///
+/// A drop priority can also be given to lazy statics with `[dynamic(lazy,drop=<prio>)]`. Such
+/// a lazy static is then dropped by a program destructor, if it has been initialized, and its drop
+/// is ordered relative to [macro@destructor] functions and to the drop of other dynamic statics
+/// with the same priority semantic. This does not require the "atexit" feature.
+///
+/// ```ignore
+/// // V3 is dropped after V4
+/// #[dynamic(lazy,drop=20)]
+/// static V3 :A = A::new(42);
+///
+/// #[dynamic(lazy,drop=10)]
+/// static V4 :A = A::new(unsafe{V3.0} - 9);
+/// ```
+///
+/// ## Fallible initialization
+///
+/// A lazy static whose initialization may fail can hold the `Result` of the initialization.
+/// `Lazy::try_get` then returns a reference to the value or to the error, which is kept and
+/// seen by every access instead of poisoning the static:
+///
+/// ```ignore
+/// #[dynamic(lazy)]
+/// static CONFIG: Result<String, std::io::Error> = std::fs::read_to_string("app.conf");
+///
+/// fn config() -> Result<&'static String, &'static std::io::Error> {
+///     Lazy::try_get(&CONFIG)
+/// }
+/// ```
+///
+/// ## Ordering assertions
+///
+/// The initialization order a lazy static relies on can be checked in debug mode with
+/// `assert_init_before="<static>"`. The initialization of the static then panics if the given
+/// lazy static is already initialized. The argument can be repeated.
+///
+/// ```ignore
+/// #[dynamic(lazy, assert_init_before = "LOGGER")]
+/// static CONFIG: Config = Config::load();
+///
+/// #[dynamic(lazy)]
+/// static LOGGER: Logger = Logger::new(&CONFIG);
+/// ```
+///
 /// ## Thread locals
 ///
 /// *lazy statics* can be declared for thread local. This feature does not require std support. 
@@ -290,6 +492,49 @@ pub fn destructor(args: TokenStream, input: TokenStream) -> TokenStream {
 /// static V2 :A = A::new(42);
 /// ```
 ///
+/// The `thread_local` attribute can also be applied through `cfg_attr`.
+///
+/// The drop of a thread local is registered when it is initialized. If it is first initialized
+/// while the thread local destructors of its thread are running, the registration fails and
+/// the option `on_late_register` selects what happens:
+///
+/// - `on_late_register = "leak"` (the default): the value is leaked and a message is written to
+///   stderr;
+/// - `on_late_register = "error"`: the value is leaked and the static is considered dropped, so
+///   `try_with` returns an error (and a dereference panics in debug mode).
+///
+/// The value can not be dropped at process exit instead, because the storage of a thread local
+/// is released when its thread exits.
+///
+/// ```ignore
+/// #[thread_local]
+/// #[dynamic(lazy,drop,on_late_register="error")]
+/// static CONN :Connection = Connection::open();
+/// ```
+///
+/// ## Placement
+///
+/// Other attributes of the static are kept on the generated static, so `#[link_section = "..."]`
+/// places the *lazy static* in a custom section. The initialization state is stored inline
+/// with the value and is placed in the same section.
+///
+/// The option `link_section` places only the value in a custom section, for example to put
+/// a large buffer in external RAM while its initialization state stays in fast memory. The
+/// static is then a `SplitLazy` whose value is stored in a separate hidden static in that
+/// section. On ELF targets, the initialization state is placed in the section
+/// `.data.static_init.phase`. This option is only supported by `#[dynamic(lazy)]` statics.
+///
+/// ```ignore
+/// #[dynamic(lazy, link_section = ".ext_ram")]
+/// static FRAME: [u8; 1 << 20] = [0; 1 << 20];
+/// ```
+///
+/// ## Statics emitted by other macros
+///
+/// The attribute can be emitted by `macro_rules` macros and derives. The hidden helper items
+/// it generates are local to the static initializer expression, and raw identifiers are accepted
+/// for static and constructor names.
+///
 /// # Dynamic statics
 ///
 /// Those statics will be initialized at program startup, without ordering, accept between those
@@ -346,6 +591,28 @@ pub fn destructor(args: TokenStream, input: TokenStream) -> TokenStream {
 /// static V2 :A = unsafe{A::new(V1.0 + 9)};
 /// ```
 ///
+/// ## C callback tables
+///
+/// The storage of a "dynamic" static has a fixed address, so a pointer to its value can be
+/// computed at compile time with `Static::as_ptr` or `ConstStatic::as_ptr` and handed to C code
+/// that expects a filled structure. The value is written in place by the constructor of the
+/// static.
+///
+/// ```ignore
+/// #[repr(C)]
+/// struct Callbacks {
+///     log: extern "C" fn(*const c_char),
+///     level: c_int,
+/// }
+///
+/// #[dynamic]
+/// static CALLBACKS: Callbacks = Callbacks { log: rust_log, level: log_level_from_env() };
+///
+/// fn register() {
+///     unsafe { sdk_set_callbacks(ConstStatic::as_ptr(&CALLBACKS)) }
+/// }
+/// ```
+///
 /// # Full syntax and dropped statics
 ///
 /// Finaly the full syntax is for the attribute is:
@@ -362,6 +629,9 @@ pub fn destructor(args: TokenStream, input: TokenStream) -> TokenStream {
 ///   "drop" [ "=" <priority> ]
 ///   "lazy"
 ///   "drop_only "=" <priority>
+///   "drop_in_debug_only"
+///   "assert_init_before" "=" <string>
+///   "on_late_register" "=" ( "\"leak\"" | "\"error\"" )
 /// ```  
 ///
 /// The macro attribute `dynamic` is equivalent to `dynamic(lazy)`
@@ -435,17 +705,16 @@ pub fn destructor(args: TokenStream, input: TokenStream) -> TokenStream {
 ///
 /// A mutable "dynamic" static declared to have type `T` are wrapped in a mutable static of type `static_init::ConstStatic<T>` 
 ///
-/// ```no_run
+/// ```ignore
 ///
 /// // V has type static_init::ConstStatic<i32>
-/// #[dynamic]
+/// #[dynamic(0)]
 /// static V :i32 = 0;
 ///
 /// // W has type static_init::Static<i32>
-/// #[dynamic]
-/// static W :i32 = 0;
+/// #[dynamic(0)]
+/// static mut W :i32 = 0;
 /// ```
-
 #[proc_macro_attribute]
 pub fn dynamic(args: TokenStream, input: TokenStream) -> TokenStream {
     let item: ItemStatic = parse_macro_input!(input);
@@ -468,26 +737,64 @@ enum DropMode {
     Dynamic(u16),
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone)]
 struct DynMode {
     init: InitMode,
     drop: DropMode,
+    drop_in_debug_only: bool,
+    assert_init_before: Vec<Path>,
+    on_late_register: Option<LateRegister>,
+    link_section: Option<LitStr>,
+}
+
+/// What a thread local does when its drop can not be registered.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum LateRegister {
+    Leak,
+    Error,
+}
+
+/// The `support_priority` cfg alias of static_init, as a predicate evaluated in the crate that
+/// uses the macro.
+fn support_priority_cfg() -> TokenStream2 {
+    let elf = Format::Elf.cfg();
+    let coff = Format::Coff.cfg();
+    quote!(any(#elf, #coff))
 }
 
-fn parse_priority(args: TokenStream) -> std::result::Result<u16, TokenStream2> {
+/// The priority argument of `constructor` and `destructor`.
+#[derive(Clone, Copy)]
+enum Priority {
+    Value(u16),
+    /// The earliest slot that is sequenced after the initializations of the runtime (or before
+    /// its finalization), that depends on the target.
+    EarliestSafe,
+}
+
+/// `first` and `last` are the priorities of the constructors (or destructors)
+/// that are run first and last.
+fn parse_priority(
+    args: TokenStream,
+    first: Priority,
+    last: Priority,
+) -> std::result::Result<Priority, TokenStream2> {
     if !args.is_empty() {
         if let Ok(n) = syn::parse(args.clone()).map_err(|e| e.to_compile_error()) {
             let n: Ident = n;
             if n == "__lazy_init" {
-                return Ok(1);
+                return Ok(Priority::Value(1));
             } else if n == "__lazy_init_finished" {
-                return Ok(0);
+                return Ok(Priority::Value(0));
+            } else if n == "first" {
+                return Ok(first);
+            } else if n == "last" {
+                return Ok(last);
             }
         }
         let lit: Lit = syn::parse(args).map_err(|e| e.to_compile_error())?;
-        parse_priority_literal(&lit)
+        parse_priority_literal(&lit).map(Priority::Value)
     } else {
-        Ok(0)
+        Ok(Priority::Value(0))
     }
 }
 
@@ -495,12 +802,12 @@ macro_rules! generate_error{
     ($span:expr => $($args:tt),*) => {
         {
         let __expand = [$(generate_error!(@expand $args)),*];
-        quote_spanned!($span => ::core::compile_error!(::core::concat!(#(#__expand),*)))
+        quote_spanned!($span => ::core::compile_error!{::core::concat!(#(#__expand),*)})
         }
     };
     ($($args:tt),*) => {{
         let __expand = [$(generate_error!(@expand $args)),*];
-        quote!(::core::compile_error!(::core::concat!(#(#__expand),*)))
+        quote!(::core::compile_error!{::core::concat!(#(#__expand),*)})
     }
     };
     (@expand $v:literal) => {
@@ -523,10 +830,39 @@ fn parse_priority_literal(lit: &Lit) -> Result<u16, TokenStream2> {
     }
 }
 
+// Keep in sync with static_init::priority
+const RUST_ENV_CAPTURE: u16 = 65535 - 99;
+const LIBSTDCXX_RESOURCES: u16 = 65535 - 100;
+
+/// An error for the priorities of the runtime initializations, only emitted when the crate that
+/// uses the macro targets an ELF plateform.
+fn check_init_priority(priority: u16, span: Span) -> TokenStream2 {
+    let error = if priority == RUST_ENV_CAPTURE {
+        generate_error!(span=>
+            "Initialization priority 65436 is the one at which the rust standard library captures \
+             program arguments and environment. Use a priority larger than 65436 \
+             (`static_init::priority::BEFORE_RUST_ENV_CAPTURE`) or lower than 65435 \
+             (`static_init::priority::AFTER_RUST_ENV_CAPTURE`).")
+    } else if priority == LIBSTDCXX_RESOURCES {
+        generate_error!(span=>
+            "Initialization priority 65435 is the one at which libstdc++ resources are \
+             initialized. Use a lower priority \
+             (`static_init::priority::AFTER_LIBSTDCXX_RESOURCES`).")
+    } else {
+        return quote!();
+    };
+    let elf = Format::Elf.cfg();
+    quote!(#[cfg(#elf)] #error)
+}
+
 fn parse_dyn_options(args: AttributeArgs) -> std::result::Result<DynMode, TokenStream2> {
     let mut opt = DynMode {
         init: InitMode::Lazy,
         drop: DropMode::None,
+        drop_in_debug_only: false,
+        assert_init_before: Vec::new(),
+        on_late_register: None,
+        link_section: None,
     };
 
     let mut init_set = false;
@@ -558,7 +894,9 @@ fn parse_dyn_options(args: AttributeArgs) -> std::result::Result<DynMode, TokenS
             Err(generate_error!($id.span()=>
                 "Unexpected attribute argument `",
                 __unexpected,
-                "`. Expected either `init[=<u16>]`, `drop[=<u16>]`, `lazy` or `drop_only=<u16>`."
+                "`. Expected either `init[=<u16>]`, `drop[=<u16>]`, `lazy`, `drop_only=<u16>`, \
+                 `drop_in_debug_only`, `assert_init_before=\"<static>\"`, \
+                 `on_late_register=\"leak\"|\"error\"` or `link_section=\"<section>\"`."
                 ))
         }
         }
@@ -583,6 +921,15 @@ fn parse_dyn_options(args: AttributeArgs) -> std::result::Result<DynMode, TokenS
                         );
                     }
                     opt.drop = DropMode::AtExit;
+                } else if id == "drop_in_debug_only" {
+                    check_no_drop!(id);
+                    if !cfg!(feature = "atexit") {
+                        return Err(
+                            generate_error!(id.span()=>"static_init crate feature `atexit` is not enabled.",id),
+                        );
+                    }
+                    opt.drop = DropMode::AtExit;
+                    opt.drop_in_debug_only = true;
                 } else if id == "lazy" {
                     check_no_init!(id);
                     opt.init = InitMode::Lazy;
@@ -598,6 +945,7 @@ fn parse_dyn_options(args: AttributeArgs) -> std::result::Result<DynMode, TokenS
                 };
                 if id == "init" {
                     check_no_init!(id);
+                    // checked by the constructor attribute of the initializer
                     let priority = parse_priority_literal(&nv.lit)?;
                     opt.init = InitMode::Dynamic(priority);
                 } else if id == "drop" {
@@ -610,6 +958,35 @@ fn parse_dyn_options(args: AttributeArgs) -> std::result::Result<DynMode, TokenS
                     let priority = parse_priority_literal(&nv.lit)?;
                     opt.init = InitMode::Const;
                     opt.drop = DropMode::Dynamic(priority);
+                } else if id == "assert_init_before" {
+                    let other = match &nv.lit {
+                        Lit::Str(s) => s.parse::<Path>().map_err(|e| e.to_compile_error())?,
+                        lit => {
+                            return Err(generate_error!(lit.span()=>
+                                "Expected the path of a lazy static as a string literal."
+                            ))
+                        }
+                    };
+                    opt.assert_init_before.push(other);
+                } else if id == "on_late_register" {
+                    opt.on_late_register = match &nv.lit {
+                        Lit::Str(s) if s.value() == "leak" => Some(LateRegister::Leak),
+                        Lit::Str(s) if s.value() == "error" => Some(LateRegister::Error),
+                        lit => {
+                            return Err(generate_error!(lit.span()=>
+                                "Expected either `\"leak\"` or `\"error\"`."
+                            ))
+                        }
+                    };
+                } else if id == "link_section" {
+                    opt.link_section = match &nv.lit {
+                        Lit::Str(s) => Some(s.clone()),
+                        lit => {
+                            return Err(generate_error!(lit.span()=>
+                                "Expected the name of a section as a string literal."
+                            ))
+                        }
+                    };
                 } else {
                     return unexpected_arg!(id);
                 }
@@ -628,38 +1005,122 @@ fn parse_dyn_options(args: AttributeArgs) -> std::result::Result<DynMode, TokenS
         Err(generate_error!(
             "static_init crate feature `lazy` is not enabled."
         ))
-    } else if opt.init == InitMode::Lazy
-        && !(opt.drop == DropMode::None || opt.drop == DropMode::AtExit)
-    {
-        Err(generate_error!("Drop mode not supported for lazy statics."))
+    } else if opt.init != InitMode::Lazy && opt.drop_in_debug_only {
+        Err(generate_error!(
+            "`drop_in_debug_only` is only supported by lazy statics."
+        ))
+    } else if opt.init != InitMode::Lazy && !opt.assert_init_before.is_empty() {
+        Err(generate_error!(
+            "`assert_init_before` is only supported by lazy statics."
+        ))
+    } else if opt.link_section.is_some() && (opt.init != InitMode::Lazy || opt.drop != DropMode::None) {
+        Err(generate_error!(
+            "`link_section` is only supported by `#[dynamic(lazy)]` statics."
+        ))
     } else {
         Ok(opt)
     }
 }
 
+/// Where the pointer to a constructor or destructor is placed.
+struct Slot {
+    /// Predicate selecting this slot in the crate that uses the macro, when the slot depends
+    /// on the target.
+    cfg:           Option<TokenStream2>,
+    section:       String,
+    /// Priority recorded in debug mode while a constructor runs.
+    init_priority: Option<u16>,
+}
+
 fn gen_ctor_dtor(
     func: ItemFn,
-    section: &str,
+    slots: &[Slot],
     func_ptr_name: &str,
     typ: TypeBareFn,
 ) -> TokenStream2 {
     let func_ptr_name = Ident::new(func_ptr_name, Span::call_site());
 
-    let section = LitStr::new(section, Span::call_site());
-
     let func_name = &func.sig.ident;
 
     let sp = func.sig.span();
+
+    let (params, args) = if typ.inputs.is_empty() {
+        (quote!(), quote!())
+    } else {
+        (
+            quote!(argc: i32, argv: *const *const u8, env: *const *const u8),
+            quote!(argc, argv, env),
+        )
+    };
+
+    // A function returning a result is called by a wrapper that passes
+    // the error to the constructor error handler.
+    let func_name: Expr = match &func.sig.output {
+        ReturnType::Type(..) => {
+            let name = LitStr::new(&func_name.unraw().to_string(), func_name.span());
+            parse_quote_spanned! {sp=>{
+                extern "C" fn __static_init_result_wrapper(#params) {
+                    #[allow(unused_unsafe)]
+                    if let Err(e) = unsafe { #func_name(#args) } {
+                        ::static_init::__constructor_error(#name, &e);
+                    }
+                }
+                __static_init_result_wrapper
+            }}
+        }
+        ReturnType::Default => parse_quote!(#func_name),
+    };
+
+    let ptrs = slots.iter().map(|slot| {
+        let section = LitStr::new(&slot.section, Span::call_site());
+
+        // In debug mode, the current initialization priority is recorded so that
+        // accesses to dynamic statics of the same priority are detected.
+        let func_name: Expr = match slot.init_priority {
+            Some(priority) => {
+                parse_quote_spanned! {sp=>{
+                    extern "C" fn __static_init_prio_wrapper(#params) {
+                        ::static_init::__set_init_prio(#priority as i32);
+                        #[allow(unused_unsafe)]
+                        unsafe { (#func_name)(#args) };
+                        ::static_init::__set_init_prio(i32::MIN);
+                    }
+                    __static_init_prio_wrapper
+                }}
+            }
+            None => func_name.clone(),
+        };
+
+        let cfg = slot.cfg.as_ref().map(|pred| quote!(#[cfg(#pred)]));
+
+        quote_spanned! {sp=>
+            #cfg
+            #[doc(hidden)]
+            #[link_section = #section]
+            #[used]
+            pub static #func_ptr_name: #typ = #func_name;
+        }
+    });
+
+    // When the slots depend on the target, targets without constructors get an error.
+    let preds: Vec<_> = slots.iter().filter_map(|slot| slot.cfg.as_ref()).collect();
+    let no_support = if preds.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            #[cfg(not(any(#(#preds),*)))]
+            ::core::compile_error!("program constructors/destructors not supported on this target");
+        })
+    };
+
     //if func.sig.unsafety.is_none() {
     //    quote_spanned! {sp=>compile_error!("Constructors and destructors must be unsafe functions as \
     //    they may access uninitialized memory regions")}
     //} else {
     quote_spanned! {sp=>
         #func
-        #[doc(hidden)]
-        #[link_section = #section]
-        #[used]
-        pub static #func_ptr_name: #typ = #func_name;
+        #(#ptrs)*
+        #no_support
     }
     //}
 }
@@ -690,6 +1151,32 @@ fn gen_dyn_init(mut stat: ItemStatic, options: DynMode) -> TokenStream2 {
              `#[thread_local]` attribute"
         );
     }
+    if is_thread_local && matches!(options.drop, DropMode::Dynamic(_)) {
+        return generate_error!(
+            "Drop priorities are not supported for `#[thread_local]` statics, use \
+             `#[dynamic(lazy,drop)]` instead"
+        );
+    }
+    if is_thread_local && options.drop_in_debug_only {
+        return generate_error!(
+            "`drop_in_debug_only` is not supported for `#[thread_local]` statics"
+        );
+    }
+    if is_thread_local && options.link_section.is_some() {
+        return generate_error!(
+            "`link_section` is not supported for `#[thread_local]` statics"
+        );
+    }
+    if is_thread_local && !options.assert_init_before.is_empty() {
+        return generate_error!(
+            "`assert_init_before` is not supported for `#[thread_local]` statics"
+        );
+    }
+    if options.on_late_register.is_some() && !(is_thread_local && options.drop == DropMode::AtExit) {
+        return generate_error!(
+            "`on_late_register` is only supported by `#[thread_local] #[dynamic(lazy,drop)]` statics"
+        );
+    }
     if is_thread_local && options.drop == DropMode::AtExit && !cfg!(feature = "thread_local_drop") {
         return generate_error!(
             "`#[thread_local] #[dynamic(lazy,drop)]` needs static_init crate `thread_local_drop` feature"
@@ -698,11 +1185,11 @@ fn gen_dyn_init(mut stat: ItemStatic, options: DynMode) -> TokenStream2 {
 
     let stat_ref: Expr = if options.init != InitMode::Lazy && stat.mutability.is_some() {
         parse_quote! {
-            &mut #stat_name
+            &mut *::core::ptr::addr_of_mut!(#stat_name)
         }
     } else {
         parse_quote! {
-            &#stat_name
+            &*::core::ptr::addr_of!(#stat_name)
         }
     };
 
@@ -714,49 +1201,81 @@ fn gen_dyn_init(mut stat: ItemStatic, options: DynMode) -> TokenStream2 {
         };
     }
 
-    let typ: Type = if options.init != InitMode::Lazy {
+    let (typ, flavor): (Type, Expr) = if options.link_section.is_some() {
+        (
+            parse_quote!(::static_init::SplitLazy::<#stat_typ>),
+            parse_quote!(::static_init::StaticFlavor::Lazy),
+        )
+    } else if options.init != InitMode::Lazy {
         if stat.mutability.is_none() {
             into_mutable!();
-            parse_quote! {
-                ::static_init::ConstStatic::<#stat_typ>
-            }
+            (
+                parse_quote!(::static_init::ConstStatic::<#stat_typ>),
+                parse_quote!(::static_init::StaticFlavor::ConstStatic),
+            )
         } else {
-            parse_quote! {
-                ::static_init::Static::<#stat_typ>
-            }
+            (
+                parse_quote!(::static_init::Static::<#stat_typ>),
+                parse_quote!(::static_init::StaticFlavor::Static),
+            )
         }
     } else if is_thread_local {
         if stat.mutability.is_none() && options.drop == DropMode::AtExit {
             into_mutable!();
-            parse_quote! {
-                ::static_init::ThreadLocalConstLazy::<#stat_typ>
-            }
+            (
+                parse_quote!(::static_init::ThreadLocalConstLazy::<#stat_typ>),
+                parse_quote!(::static_init::StaticFlavor::ThreadLocalConstLazy),
+            )
         } else {
-            parse_quote! {
-                ::static_init::ThreadLocalLazy::<#stat_typ>
-            }
+            (
+                parse_quote!(::static_init::ThreadLocalLazy::<#stat_typ>),
+                parse_quote!(::static_init::StaticFlavor::ThreadLocalLazy),
+            )
         }
+    } else if stat.mutability.is_none() && options.drop != DropMode::None {
+        into_mutable!();
+        (
+            parse_quote!(::static_init::ConstLazy::<#stat_typ>),
+            parse_quote!(::static_init::StaticFlavor::ConstLazy),
+        )
     } else {
-        if stat.mutability.is_none() && options.drop == DropMode::AtExit {
-            into_mutable!();
-            parse_quote! {
-                ::static_init::ConstLazy::<#stat_typ>
-            }
-        } else {
-            parse_quote! {
-                ::static_init::Lazy::<#stat_typ>
-            }
-        }
+        (
+            parse_quote!(::static_init::Lazy::<#stat_typ>),
+            parse_quote!(::static_init::StaticFlavor::Lazy),
+        )
     };
 
     let sp = stat.expr.span();
 
+    // In debug mode, the statics that must be initialized after this one are
+    // checked when the initialization of this one ends.
+    let expr: Expr = if cfg!(debug_mode) && !options.assert_init_before.is_empty() {
+        let others = &options.assert_init_before;
+        parse_quote_spanned! {sp=>{
+            let __static_init_value = #expr;
+            #(
+            #[allow(unused_unsafe)]
+            ::static_init::debug::__assert_not_initialized(
+                unsafe { &*::core::ptr::addr_of!(#others) },
+                ::core::stringify!(#stat_name),
+                ::core::stringify!(#others),
+            );
+            )*
+            __static_init_value
+        }}
+    } else {
+        expr.clone()
+    };
+    let expr = &expr;
+
     let initer = match options.init {
         InitMode::Dynamic(priority) if options.drop == DropMode::AtExit => {
             let attr: Attribute = parse_quote!(#[::static_init::constructor(#priority)]);
             Some(quote_spanned! {sp=>
                     extern "C" fn __static_init_dropper() {
-                        unsafe{#typ::drop(#stat_ref)}
+                        if !::static_init::is_fast_exit() {
+                            unsafe{#typ::drop(#stat_ref)}
+                        }
                     }
                     #attr
                     extern "C" fn __static_init_initializer() {
@@ -782,27 +1301,48 @@ fn gen_dyn_init(mut stat: ItemStatic, options: DynMode) -> TokenStream2 {
             })
         }
 
-        InitMode::Lazy => Some(quote_spanned! {sp=>
+        // Lazy statics are only initialized before main on targets that support
+        // constructor priorities; elsewhere they are plain lazy statics.
+        InitMode::Lazy => {
+            let support_priority = support_priority_cfg();
+            Some(quote_spanned! {sp=>
+                #[cfg(#support_priority)]
                 #[::static_init::constructor(__lazy_init)]
                 extern "C" fn __static_init_initializer() {
                     #[allow(unused_unsafe)]
                     unsafe {#typ::__do_init(#stat_ref)};
                 }
-        }),
+            })
+        }
 
         InitMode::Const => None,
     };
 
-    let droper = if let DropMode::Dynamic(priority) = options.drop {
-        let attr: Attribute = parse_quote!(#[::static_init::destructor(#priority)]);
-        Some(quote_spanned! {sp=>
-                #attr
-                extern "C" fn __static_init_droper() {
-                    unsafe {#typ::drop(#stat_ref)}
-                }
-        })
-    } else {
-        None
+    let droper = match options.drop {
+        DropMode::Dynamic(priority) if options.init == InitMode::Lazy => {
+            let attr: Attribute = parse_quote!(#[::static_init::destructor(#priority)]);
+            Some(quote_spanned! {sp=>
+                    #attr
+                    extern "C" fn __static_init_droper() {
+                        let __static_init_lazy = unsafe {#stat_ref};
+                        if #typ::is_initialized(__static_init_lazy) && !::static_init::is_fast_exit() {
+                            unsafe {#typ::drop(__static_init_lazy)}
+                        }
+                    }
+            })
+        }
+        DropMode::Dynamic(priority) => {
+            let attr: Attribute = parse_quote!(#[::static_init::destructor(#priority)]);
+            Some(quote_spanned! {sp=>
+                    #attr
+                    extern "C" fn __static_init_droper() {
+                        if !::static_init::is_fast_exit() {
+                            unsafe {#typ::drop(#stat_ref)}
+                        }
+                    }
+            })
+        }
+        _ => None,
     };
 
     let statid = &stat.ident;
@@ -815,6 +1355,13 @@ fn gen_dyn_init(mut stat: ItemStatic, options: DynMode) -> TokenStream2 {
 
     let drop_priority: Expr = match options.drop {
         DropMode::Dynamic(n) => parse_quote!(::static_init::DropMode::Dynamic(#n)),
+        DropMode::AtExit if options.drop_in_debug_only => parse_quote!(
+            if ::core::cfg!(debug_assertions) {
+                ::static_init::DropMode::AtExit
+            } else {
+                ::static_init::DropMode::None
+            }
+        ),
         DropMode::AtExit => parse_quote!(::static_init::DropMode::AtExit),
         DropMode::None => parse_quote!(::static_init::DropMode::None),
     };
@@ -826,6 +1373,9 @@ fn gen_dyn_init(mut stat: ItemStatic, options: DynMode) -> TokenStream2 {
         file_name: ::core::file!(),
         line: ::core::line!(),
         column: ::core::column!(),
+        type_name: ::core::stringify!(#stat_typ),
+        size: ::core::mem::size_of::<#stat_typ>(),
+        flavor: #flavor,
         init_mode: #init_priority,
         drop_mode: #drop_priority
         })) 
@@ -842,23 +1392,67 @@ fn gen_dyn_init(mut stat: ItemStatic, options: DynMode) -> TokenStream2 {
             }
             }
         }
+        InitMode::Lazy if options.link_section.is_some() => {
+            let section = options.link_section.as_ref().unwrap();
+            let elf = Format::Elf.cfg();
+            // The initialization state stays in a data section.
+            stat.attrs
+                .push(parse_quote!(#[cfg_attr(#elf, link_section = ".data.static_init.phase")]));
+            quote_spanned! {sp=>{
+                #[link_section = #section]
+                static __STATIC_INIT_STORAGE: ::static_init::StaticStorage<#stat_typ> =
+                    ::static_init::StaticStorage::uninit();
+                #initer
+                #typ::new(&__STATIC_INIT_STORAGE, || {#expr})
+            }
+            }
+        }
+        InitMode::Lazy if matches!(options.drop, DropMode::Dynamic(_)) => {
+            quote_spanned! {sp=>{
+                #initer
+                #droper
+                #typ::new(
+                    || {
+                        let v = #expr;
+                        unsafe{#typ::__drop_registered(#stat_ref)};
+                        v
+                        },
+                    #static_info
+                    )
+            }
+            }
+        }
         InitMode::Lazy if !(options.drop == DropMode::AtExit) => {
             quote_spanned! {sp=>{
                 #initer
+                #droper
                 #typ::new(|| {#expr},#static_info)
             }
             }
         }
         InitMode::Lazy if !is_thread_local => {
+            // The condition is evaluated in the crate of the static.
+            let register: Expr = if options.drop_in_debug_only {
+                parse_quote!(
+                    ::core::cfg!(debug_assertions) && !::static_init::is_finalization_disabled()
+                )
+            } else {
+                parse_quote!(!::static_init::is_finalization_disabled())
+            };
             quote_spanned! {sp=>{
                 extern "C" fn __static_init_dropper() {
-                    unsafe{::core::ptr::drop_in_place(#typ::as_mut_ptr(#stat_ref))}
+                    if !::static_init::is_fast_exit() {
+                        unsafe{#typ::drop(#stat_ref)}
+                    }
                 }
                 #initer
                 #typ::new(
                     || {
-                        let v = (|| {#expr})();
-                        unsafe{::libc::atexit(__static_init_dropper)};
+                        let v = #expr;
+                        if #register {
+                            unsafe{::libc::atexit(__static_init_dropper)};
+                            unsafe{#typ::__drop_registered(#stat_ref)};
+                        }
                         v
                         },
                     #static_info
@@ -867,15 +1461,21 @@ fn gen_dyn_init(mut stat: ItemStatic, options: DynMode) -> TokenStream2 {
         }
         InitMode::Lazy => {
             //thread local drop
+            let on_late_register = match options.on_late_register.unwrap_or(LateRegister::Leak) {
+                LateRegister::Leak => quote!(::static_init::__leak_late_tls(::core::stringify!(#statid))),
+                LateRegister::Error => quote!(#typ::__mark_dropped(unsafe{#stat_ref})),
+            };
             quote_spanned! {sp=>{
                 fn __static_init_dropper() {
-                    unsafe{::core::ptr::drop_in_place(#typ::as_mut_ptr(#stat_ref))}
+                    unsafe{#typ::drop(#stat_ref)}
                 }
                 #initer
                 #typ::new(
                     || {
-                        let v = (|| {#expr})();
-                        unsafe{::static_init::__push_tls_destructor(__static_init_dropper)};
+                        let v = #expr;
+                        if !unsafe{::static_init::__push_tls_destructor(__static_init_dropper)} {
+                            #on_late_register;
+                        }
                         v
                         },
                     #static_info
@@ -900,6 +1500,11 @@ fn gen_dyn_init(mut stat: ItemStatic, options: DynMode) -> TokenStream2 {
 
     *stat.ty = typ;
 
+    if options.init == InitMode::Lazy {
+        // the block only holds an expression when the initializer is configured out
+        stat.attrs.push(parse_quote!(#[allow(unused_braces)]));
+    }
+
     quote_spanned! {sp=>
 
     #stat
@@ -0,0 +1,39 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg(feature = "atexit")]
+
+use static_init::{at_exit, destructor};
+use std::sync::Mutex;
+
+static ORDER: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+fn record(i: u32) {
+    ORDER.lock().unwrap().push(i)
+}
+
+// Destructors are run after the functions registered with atexit.
+#[destructor]
+extern "C" fn check_order() {
+    let ok = *ORDER.lock().unwrap() == [1, 3, 4, 2] && at_exit(0, || ()).is_err();
+    if !ok {
+        // exit status is checked by cargo test
+        unsafe { libc::_exit(1) }
+    }
+}
+
+#[test]
+fn run_by_priority() {
+    at_exit(10, || record(2)).unwrap();
+    at_exit(0, || record(1)).unwrap();
+    at_exit(10, || {
+        record(3);
+        // registered while the handlers are run
+        at_exit(10, || record(4)).unwrap();
+    })
+    .unwrap();
+}
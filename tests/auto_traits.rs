@@ -0,0 +1,63 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use static_init::{ConstStatic, Static, StaticStorage};
+use std::panic::{RefUnwindSafe, UnwindSafe};
+
+fn assert_send<T: ?Sized + Send>() {}
+fn assert_sync<T: ?Sized + Sync>() {}
+fn assert_unwind_safe<T: ?Sized + UnwindSafe>() {}
+fn assert_ref_unwind_safe<T: ?Sized + RefUnwindSafe>() {}
+
+#[test]
+fn statics() {
+    assert_send::<Static<Vec<i32>>>();
+    assert_sync::<Static<Vec<i32>>>();
+    assert_unwind_safe::<Static<Vec<i32>>>();
+    assert_ref_unwind_safe::<Static<Vec<i32>>>();
+
+    assert_send::<ConstStatic<Vec<i32>>>();
+    assert_sync::<ConstStatic<Vec<i32>>>();
+    assert_unwind_safe::<ConstStatic<Vec<i32>>>();
+    assert_ref_unwind_safe::<ConstStatic<Vec<i32>>>();
+
+    assert_send::<StaticStorage<Vec<i32>>>();
+    assert_sync::<StaticStorage<Vec<i32>>>();
+}
+
+#[cfg(feature = "lazy")]
+#[test]
+fn lazies() {
+    use static_init::{AnyLazyStatic, ConstLazy, Lazy, Once};
+
+    assert_send::<Lazy<Vec<i32>>>();
+    assert_sync::<Lazy<Vec<i32>>>();
+    assert_unwind_safe::<Lazy<Vec<i32>>>();
+    assert_ref_unwind_safe::<Lazy<Vec<i32>>>();
+
+    assert_send::<ConstLazy<Vec<i32>>>();
+    assert_sync::<ConstLazy<Vec<i32>>>();
+    assert_unwind_safe::<ConstLazy<Vec<i32>>>();
+    assert_ref_unwind_safe::<ConstLazy<Vec<i32>>>();
+
+    assert_sync::<dyn AnyLazyStatic>();
+
+    assert_send::<Once>();
+    assert_sync::<Once>();
+    assert_ref_unwind_safe::<Once>();
+}
+
+#[test]
+fn thread_locals() {
+    use static_init::{AccessError, ThreadLocalConstLazy, ThreadLocalLazy};
+
+    assert_unwind_safe::<ThreadLocalLazy<Vec<i32>>>();
+    assert_unwind_safe::<ThreadLocalConstLazy<Vec<i32>>>();
+
+    assert_send::<AccessError>();
+    assert_sync::<AccessError>();
+}
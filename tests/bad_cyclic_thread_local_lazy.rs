@@ -15,24 +15,25 @@ mod test {
     #[dynamic(lazy)]
     static mut V0: i32 = unsafe{*V0};
 
-    fn panic_hook(p: &core::panic::PanicInfo<'_>) -> () {
+    fn panic_hook(p: &dyn std::fmt::Display) {
         println!("Panic caught {}", p);
         std::process::exit(0)
     }
 
     #[constructor(10)]
     extern "C" fn set_hook() {
-        std::panic::set_hook(Box::new(panic_hook));
+        std::panic::set_hook(Box::new(|p| panic_hook(p)));
     }
 }
 
-fn panic_hook(p: &core::panic::PanicInfo<'_>) -> () {
+fn panic_hook(p: &dyn std::fmt::Display) {
     println!("Panic caught {}", p);
     std::process::exit(1)
 }
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn bad_cyclic_thread_local_lazy() {
-    std::panic::set_hook(Box::new(panic_hook));
+    std::panic::set_hook(Box::new(|p| panic_hook(p)));
     assert!(!cfg!(all(debug_mode,feature = "test_thread_local")));
 }
@@ -21,19 +21,19 @@ mod test {
     impl Drop for A {
         fn drop(&mut self) {
             if self.0 {
-                unsafe{&*V0};
+                let _ = unsafe{&*V0};
             }
         }
     }
 
-    fn panic_hook(p: &core::panic::PanicInfo<'_>) -> () {
+    fn panic_hook(p: &dyn std::fmt::Display) {
         println!("Panic caught {}", p);
-        std::process::exit(0)
+        unsafe{libc::_exit(0)}
     }
 
     #[destructor(0)]
     extern "C" fn set_hook() {
-        std::panic::set_hook(Box::new(panic_hook));
+        std::panic::set_hook(Box::new(|p| panic_hook(p)));
     }
 
     #[destructor(30)]
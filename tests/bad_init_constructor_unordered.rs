@@ -0,0 +1,42 @@
+// Copyright 2021 Olivier Kannengieser 
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#[cfg(debug_mode)]
+mod test {
+    use static_init::{constructor, dynamic};
+
+    #[dynamic(10)]
+    static V0: i32 = 12;
+
+    #[constructor(10)]
+    extern "C" fn access_v0() {
+        let _ = unsafe { *V0 };
+    }
+
+    fn panic_hook(p: &dyn std::fmt::Display) {
+        println!("Panic caught {}", p);
+        std::process::exit(0)
+    }
+
+    #[constructor(200)]
+    extern "C" fn set_hook() {
+        std::panic::set_hook(Box::new(|p| panic_hook(p)));
+    }
+}
+
+fn panic_hook(p: &dyn std::fmt::Display) {
+    println!("Panic caught {}", p);
+    std::process::exit(1)
+}
+
+
+#[test]
+#[allow(clippy::assertions_on_constants)]
+fn bad_init_constructor_unordered() {
+    std::panic::set_hook(Box::new(|p| panic_hook(p)));
+    assert!(!cfg!(debug_mode));
+}
@@ -0,0 +1,53 @@
+// Copyright 2021 Olivier Kannengieser 
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg_attr(feature = "test_thread_local",feature(thread_local))]
+
+#[cfg(all(feature = "test_thread_local", feature = "thread_local_drop", debug_mode))]
+mod test {
+    use static_init::{constructor, dynamic};
+
+    struct A(bool);
+
+    impl Drop for A {
+        fn drop(&mut self) {
+            if self.0 {
+                let _ = unsafe{&*V0};
+            }
+        }
+    }
+
+    #[thread_local]
+    #[dynamic(lazy,drop)]
+    static V0: A = A(false);
+
+    #[thread_local]
+    #[dynamic(lazy,drop)]
+    static V1: A = A(true);
+
+    fn panic_hook(p: &dyn std::fmt::Display) {
+        println!("Panic caught {}", p);
+        std::process::exit(0)
+    }
+
+    #[constructor(10)]
+    extern "C" fn set_hook() {
+        std::panic::set_hook(Box::new(|p| panic_hook(p)));
+    }
+
+    #[test]
+    fn bad_thread_local_access_after_drop() {
+        std::thread::spawn(|| unsafe {
+            let _ = &*V0;
+            let _ = &*V1;
+        })
+        .join()
+        .unwrap();
+        println!("No panic happened :(");
+        std::process::exit(1)
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use static_init::{caps, sections};
+
+#[test]
+fn consistent_with_sections() {
+    assert_eq!(caps::HAS_CTOR, sections::init_section(0).is_some());
+    assert_eq!(caps::HAS_CTOR_PRIORITY, sections::init_section(10).is_some());
+    assert_eq!(caps::HAS_ATEXIT, cfg!(feature = "atexit"));
+    assert_eq!(caps::HAS_THREAD_EXIT, cfg!(feature = "thread_local_drop"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[allow(clippy::assertions_on_constants)]
+fn linux() {
+    assert!(caps::HAS_CTOR && caps::HAS_CTOR_PRIORITY);
+    assert_eq!(caps::HAS_LESSER_LAZY, cfg!(not(feature = "test_no_global_lazy_hint")));
+}
+
+// The path without constructors is checked by building this test for a target without
+// constructor priorities:
+// cargo build --target wasm32-unknown-unknown --no-default-features --features macros,lazy --test caps
+#[cfg(all(feature = "lazy", feature = "macros"))]
+#[test]
+fn lazy_initialized_before_main_with_priorities() {
+    use static_init::{dynamic, Lazy};
+
+    #[dynamic(lazy)]
+    static L: Vec<i32> = vec![1];
+
+    assert_eq!(Lazy::is_initialized(&L), caps::HAS_CTOR_PRIORITY);
+    assert_eq!(L.len(), 1);
+    assert!(Lazy::is_initialized(&L));
+}
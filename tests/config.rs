@@ -0,0 +1,41 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use static_init::{disable_finalization, is_fast_exit, is_finalization_disabled, Config};
+use std::sync::Mutex;
+
+// the settings are global and tests are run concurrently
+static SETTINGS: Mutex<()> = Mutex::new(());
+
+#[test]
+fn apply() {
+    let _lock = SETTINGS.lock().unwrap();
+
+    Config::new().fast_exit(true).disable_finalization(true).apply();
+    assert!(is_fast_exit());
+    assert!(is_finalization_disabled());
+
+    // settings that are not given are unchanged
+    Config::new().disable_finalization(false).apply();
+    assert!(is_fast_exit());
+    assert!(!is_finalization_disabled());
+
+    Config::new().fast_exit(false).apply();
+    assert!(!is_fast_exit());
+    assert!(!is_finalization_disabled());
+}
+
+#[test]
+fn default_config_keeps_disabled_finalization() {
+    let _lock = SETTINGS.lock().unwrap();
+
+    disable_finalization();
+    Config::default().apply();
+    assert!(is_finalization_disabled());
+
+    Config::new().disable_finalization(false).apply();
+}
@@ -0,0 +1,40 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use static_init::{
+    constructor, constructor_error_count, set_constructor_error_handler, ConstructorError,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static HANDLED: AtomicBool = AtomicBool::new(false);
+
+fn handler(e: &ConstructorError<'_>) {
+    assert_eq!(e.function, "failing");
+    assert_eq!(format!("{:?}", e.error), "\"failed\"");
+    HANDLED.store(true, Ordering::Relaxed);
+}
+
+#[constructor(20)]
+extern "C" fn set_handler() {
+    set_constructor_error_handler(handler);
+}
+
+#[constructor(10)]
+fn failing() -> Result<(), &'static str> {
+    Err("failed")
+}
+
+#[constructor(10)]
+fn succeeding() -> Result<(), &'static str> {
+    Ok(())
+}
+
+#[test]
+fn error_is_handled() {
+    assert!(HANDLED.load(Ordering::Relaxed));
+    assert_eq!(constructor_error_count(), 1);
+}
@@ -0,0 +1,59 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use static_init::{defer_at_exit, defer_at_thread_exit};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static AT_EXIT: AtomicBool = AtomicBool::new(false);
+
+#[test]
+fn at_exit() {
+    // run last: check that the block registered after it was run
+    defer_at_exit! {
+        if !AT_EXIT.load(Ordering::Relaxed) {
+            unsafe { libc::_exit(1) }
+        }
+    }
+    defer_at_exit! {
+        AT_EXIT.store(true, Ordering::Relaxed);
+    }
+}
+
+static AT_THREAD_EXIT: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn at_thread_exit() {
+    std::thread::spawn(|| {
+        defer_at_thread_exit! {
+            assert_eq!(AT_THREAD_EXIT.fetch_add(1, Ordering::Relaxed), 0);
+        };
+        let registered = defer_at_thread_exit! {
+            assert_eq!(AT_THREAD_EXIT.fetch_add(1, Ordering::Relaxed), 1);
+        };
+        assert!(registered);
+    })
+    .join()
+    .unwrap();
+    assert_eq!(AT_THREAD_EXIT.load(Ordering::Relaxed), 2);
+}
+
+static LATE: AtomicBool = AtomicBool::new(false);
+
+#[test]
+fn registered_while_thread_exits() {
+    std::thread::spawn(|| {
+        defer_at_thread_exit! {
+            let registered = defer_at_thread_exit! {
+                LATE.store(true, Ordering::Relaxed);
+            };
+            assert!(!registered);
+        };
+    })
+    .join()
+    .unwrap();
+    assert!(!LATE.load(Ordering::Relaxed));
+}
@@ -0,0 +1,34 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![cfg(all(feature = "lazy", feature = "atexit"))]
+
+use static_init::{constructor, disable_finalization, dynamic, is_finalization_disabled};
+
+struct A(i32);
+
+impl Drop for A {
+    fn drop(&mut self) {
+        // exit status is checked by cargo test
+        unsafe { libc::_exit(1) }
+    }
+}
+
+#[dynamic(lazy, drop)]
+static V: A = A(1);
+
+// lesser lazy statics are initialized by constructors of priority 1
+#[constructor(10)]
+extern "C" fn early() {
+    assert!(!is_finalization_disabled());
+    disable_finalization();
+}
+
+#[test]
+fn drop_not_registered() {
+    assert!(is_finalization_disabled());
+    assert_eq!(unsafe { V.0 }, 1);
+}
@@ -0,0 +1,81 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+// The pending drops are checked at exit: this test binary fails if a static below is not
+// dropped exactly once.
+#![cfg(all(debug_mode, feature = "debug_drop_count", feature = "lazy", feature = "atexit"))]
+
+use static_init::{dynamic, DropMode, InitMode, Lazy, StaticFlavor, StaticInfo};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::process::Command;
+
+#[dynamic(lazy, drop)]
+static AT_EXIT: Vec<i32> = vec![1];
+
+#[dynamic(lazy, drop = 10)]
+static PRIORITIZED: Vec<i32> = vec![2];
+
+#[dynamic(0, drop)]
+static DYNAMIC: Vec<i32> = vec![3];
+
+#[dynamic(init = 0, drop = 0)]
+static DYNAMIC_PRIORITIZED: Vec<i32> = vec![4];
+
+const LEAKED: StaticInfo = StaticInfo {
+    variable_name: "LEAKED",
+    file_name:     file!(),
+    line:          line!(),
+    column:        column!(),
+    type_name:     "Vec<i32>",
+    size:          core::mem::size_of::<Vec<i32>>(),
+    flavor:        StaticFlavor::Lazy,
+    init_mode:     InitMode::Lazy,
+    drop_mode:     DropMode::AtExit,
+};
+
+// Lazies created after main must be initialized explicitly.
+fn leaked(f: fn() -> Vec<i32>) -> &'static Lazy<Vec<i32>> {
+    let l = Box::leak(Box::new(Lazy::new(f, LEAKED)));
+    Lazy::__do_init(l);
+    l
+}
+
+#[test]
+fn scheduled_drops() {
+    assert_eq!(
+        unsafe { [AT_EXIT[0], PRIORITIZED[0], DYNAMIC[0], DYNAMIC_PRIORITIZED[0]] },
+        [1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn double_drop_is_reported() {
+    let l = leaked(|| vec![5]);
+    assert_eq!(l[0], 5);
+    unsafe { Lazy::drop(l) };
+    let err = catch_unwind(AssertUnwindSafe(|| unsafe { Lazy::drop(l) })).unwrap_err();
+    let msg = err.downcast_ref::<String>().unwrap();
+    assert!(msg.contains("LEAKED") && msg.contains("dropped twice"), "{}", msg);
+}
+
+#[test]
+fn pending_drop_is_reported() {
+    if std::env::var_os("STATIC_INIT_LEAK_DROP").is_some() {
+        let l = leaked(|| vec![6]);
+        assert_eq!(l[0], 6);
+        Lazy::__drop_registered(l);
+        return;
+    }
+    let out = Command::new(std::env::current_exe().unwrap())
+        .args(["pending_drop_is_reported", "--exact"])
+        .env("STATIC_INIT_LEAK_DROP", "1")
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("1 statics whose drop was scheduled were not dropped"), "{}", stderr);
+}
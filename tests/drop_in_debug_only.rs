@@ -0,0 +1,38 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg(all(feature = "lazy", feature = "atexit"))]
+
+use static_init::{destructor, dynamic};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DROPPED: AtomicBool = AtomicBool::new(false);
+
+struct A(i32);
+
+impl Drop for A {
+    fn drop(&mut self) {
+        DROPPED.store(true, Ordering::Relaxed)
+    }
+}
+
+#[dynamic(lazy, drop_in_debug_only)]
+static V: A = A(1);
+
+// Destructors are run after the functions registered with atexit.
+#[destructor]
+extern "C" fn check_drop() {
+    if DROPPED.load(Ordering::Relaxed) != cfg!(debug_assertions) {
+        // exit status is checked by cargo test
+        unsafe { libc::_exit(1) }
+    }
+}
+
+#[test]
+fn drop_depends_on_debug_assertions() {
+    assert_eq!(unsafe { V.0 }, 1);
+}
@@ -0,0 +1,44 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg(feature = "atexit")]
+
+use static_init::{destructor, DropRegistry};
+use std::sync::Mutex;
+
+static ORDER: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+static EARLY: DropRegistry = DropRegistry::new(0);
+
+static LATE: DropRegistry = DropRegistry::new(10);
+
+struct Record(u32);
+
+impl Drop for Record {
+    fn drop(&mut self) {
+        ORDER.lock().unwrap().push(self.0)
+    }
+}
+
+// Destructors are run after the functions registered with atexit.
+#[destructor]
+extern "C" fn check_order() {
+    let ok = *ORDER.lock().unwrap() == [2, 1, 4, 3] && EARLY.register(Record(5)).is_err();
+    if !ok {
+        // exit status is checked by cargo test
+        unsafe { libc::_exit(1) }
+    }
+}
+
+#[test]
+fn dropped_by_priority() {
+    LATE.register(Record(3)).unwrap();
+    EARLY.register(Record(1)).unwrap();
+    LATE.register(Record(4)).unwrap();
+    EARLY.register(Record(2)).unwrap();
+    assert!(ORDER.lock().unwrap().is_empty());
+}
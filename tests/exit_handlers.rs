@@ -0,0 +1,29 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg(feature = "alloc")]
+
+use static_init::{push_exit_handler, run_exit_handlers};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn handlers_run_in_reverse_order() {
+    let order = Arc::new(Mutex::new(vec![]));
+    for i in 0..3 {
+        let order = order.clone();
+        push_exit_handler(move || order.lock().unwrap().push(i));
+    }
+    let o = order.clone();
+    push_exit_handler(move || {
+        let o2 = o.clone();
+        push_exit_handler(move || o2.lock().unwrap().push(10));
+    });
+    run_exit_handlers();
+    assert_eq!(*order.lock().unwrap(), vec![10, 2, 1, 0]);
+    run_exit_handlers();
+    assert_eq!(order.lock().unwrap().len(), 4);
+}
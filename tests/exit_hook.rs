@@ -0,0 +1,42 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg(feature = "atexit")]
+
+use static_init::{destructor, register_exit_hook, ExitHook};
+use std::sync::Mutex;
+
+static ORDER: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+struct Hook(u32);
+
+impl ExitHook for Hook {
+    fn on_exit(&self) {
+        ORDER.lock().unwrap().push(self.0)
+    }
+}
+
+static FIRST: Hook = Hook(1);
+
+static SECOND: Hook = Hook(2);
+
+// Destructors are run after the functions registered with atexit.
+#[destructor]
+extern "C" fn check_order() {
+    let ok = *ORDER.lock().unwrap() == [1, 2] && register_exit_hook(&FIRST, 0).is_err();
+    if !ok {
+        // exit status is checked by cargo test
+        unsafe { libc::_exit(1) }
+    }
+}
+
+#[test]
+fn notified_by_priority() {
+    register_exit_hook(&SECOND, 10).unwrap();
+    register_exit_hook(&FIRST, 0).unwrap();
+    assert!(ORDER.lock().unwrap().is_empty());
+}
@@ -0,0 +1,49 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![allow(static_mut_refs)]
+
+use static_init::{dynamic, is_fast_exit, set_fast_exit};
+
+struct A(i32);
+
+impl Drop for A {
+    fn drop(&mut self) {
+        // exit status is checked by cargo test
+        unsafe { libc::_exit(1) }
+    }
+}
+
+#[dynamic(init = 0, drop = 0)]
+static V0: A = A(1);
+
+#[cfg(feature = "lazy")]
+#[dynamic(lazy, drop = 0)]
+static V1: A = A(2);
+
+#[cfg(all(feature = "lazy", feature = "atexit"))]
+#[dynamic(lazy, drop)]
+static V2: A = A(3);
+
+#[cfg(feature = "atexit")]
+#[dynamic(0, drop)]
+static V3: A = A(4);
+
+#[test]
+fn drops_are_skipped() {
+    unsafe {
+        assert_eq!(V0.0, 1);
+        #[cfg(feature = "lazy")]
+        assert_eq!(V1.0, 2);
+        #[cfg(all(feature = "lazy", feature = "atexit"))]
+        assert_eq!(V2.0, 3);
+        #[cfg(feature = "atexit")]
+        assert_eq!(V3.0, 4);
+    }
+    assert!(!is_fast_exit());
+    set_fast_exit(true);
+    assert!(is_fast_exit());
+}
@@ -0,0 +1,46 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg_attr(feature = "test_thread_local", feature(thread_local))]
+#![cfg(all(unix, feature = "thread_local_drop", feature = "test_thread_local"))]
+
+use core::ffi::c_void;
+use core::ptr;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use static_init::dynamic;
+
+static DROP_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+struct A(i32);
+
+impl Drop for A {
+    fn drop(&mut self) {
+        DROP_COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[thread_local]
+#[dynamic(lazy, drop)]
+static X: A = A(42);
+
+extern "C" fn thread_main(_: *mut c_void) -> *mut c_void {
+    assert_eq!(unsafe { X.0 }, 42);
+    ptr::null_mut()
+}
+
+#[test]
+fn thread_local_dropped_on_foreign_thread_exit() {
+    unsafe {
+        let mut th: libc::pthread_t = core::mem::zeroed();
+        assert_eq!(
+            libc::pthread_create(&mut th, ptr::null(), thread_main, ptr::null_mut()),
+            0
+        );
+        assert_eq!(libc::pthread_join(th, ptr::null_mut()), 0);
+    }
+    assert_eq!(DROP_COUNT.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,60 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use static_init::Lazy;
+
+fn failing_init() -> i32 {
+    panic!("init failed")
+}
+
+#[cfg(not(debug_mode))]
+static FAILING: Lazy<i32> = Lazy::new(failing_init);
+
+#[cfg(debug_mode)]
+static FAILING: Lazy<i32> = Lazy::new(
+    failing_init,
+    static_init::StaticInfo {
+        variable_name: "FAILING",
+        file_name:     file!(),
+        line:          line!(),
+        column:        column!(),
+        type_name:     "i32",
+        size:          4,
+        flavor:        static_init::StaticFlavor::Lazy,
+        init_mode:     static_init::InitMode::Lazy,
+        drop_mode:     static_init::DropMode::None,
+    },
+);
+
+#[test]
+fn payload_is_returned() {
+    let e = Lazy::force_with_unwind(&FAILING).unwrap_err();
+    assert_eq!(e.downcast_ref::<&str>(), Some(&"init failed"));
+    assert!(!Lazy::is_initialized(&FAILING));
+    // The lazy is poisoned.
+    let e = Lazy::force_with_unwind(&FAILING).unwrap_err();
+    let msg = e
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| e.downcast_ref::<&str>().copied())
+        .unwrap();
+    if cfg!(debug_mode) {
+        assert!(msg.starts_with("The initialization of"), "{}", msg);
+    } else {
+        assert!(msg.contains("poisoned"), "{}", msg);
+    }
+}
+
+#[test]
+fn value_is_returned() {
+    use static_init::dynamic;
+
+    #[dynamic(lazy)]
+    static OK: i32 = 3;
+
+    assert_eq!(Lazy::force_with_unwind(&OK).ok(), Some(&3));
+}
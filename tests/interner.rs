@@ -0,0 +1,49 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use static_init::{intern, Interner};
+
+#[test]
+fn concurrent_interning() {
+    static IDS: Interner<[u8]> = Interner::new();
+
+    let ptrs: Vec<usize> = std::thread::scope(|s| {
+        let hs: Vec<_> = (0..4)
+            .map(|_| s.spawn(|| IDS.intern(&[1, 2, 3]).as_ptr() as usize))
+            .collect();
+        hs.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert!(ptrs.iter().all(|p| *p == ptrs[0]));
+    assert_eq!(IDS.len(), 1);
+    assert_eq!(IDS.get(&[1, 2, 3]), Some(&[1u8, 2, 3][..]));
+    assert_eq!(IDS.get(&[4]), None);
+}
+
+#[test]
+fn global_strings() {
+    let a = intern!("global");
+    let b = intern!(&format!("glo{}", "bal"));
+    assert!(core::ptr::eq(a, b));
+    assert!(!core::ptr::eq(a, intern!("other")));
+}
+
+#[test]
+fn sized_values() {
+    static NUMBERS: Interner<u32> = Interner::new();
+
+    let a = NUMBERS.intern(&42);
+    assert!(core::ptr::eq(a, NUMBERS.intern(&42)));
+    assert_eq!(NUMBERS.get(&7), None);
+    assert_eq!(NUMBERS.len(), 1);
+}
+
+#[test]
+fn literal_is_cached() {
+    let ptrs: Vec<_> = (0..3).map(|_| intern!("cached").as_ptr()).collect();
+    assert!(ptrs.iter().all(|p| *p == ptrs[0]));
+    assert!(core::ptr::eq(intern!("cached"), intern!(&String::from("cached"))));
+}
@@ -0,0 +1,45 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use static_init::LazySlab;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static SLAB: LazySlab<usize, 3> = LazySlab::new();
+
+#[test]
+fn slots_are_initialized_once() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                let v = SLAB.get_or_init(2, || CALLS.fetch_add(1, Ordering::Relaxed) + 10);
+                assert_eq!(*v, 10);
+            });
+        }
+    });
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+    assert_eq!(SLAB.get(2), Some(&10));
+    assert_eq!(SLAB.get(0), None);
+    assert_eq!(SLAB.get(3), None);
+    assert_eq!(SLAB.len(), 3);
+}
+
+#[test]
+fn initialized_slots_are_dropped() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    struct D;
+    impl Drop for D {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    let slab: LazySlab<D, 4> = LazySlab::new();
+    slab.get_or_init(0, || D);
+    slab.get_or_init(3, || D);
+    drop(slab);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+}
@@ -0,0 +1,59 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg(all(target_os = "linux", feature = "lazy"))]
+
+use static_init::{constructor, dynamic, SplitLazy};
+use std::sync::Mutex;
+
+static EVENTS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+fn record(event: &'static str) {
+    EVENTS.lock().unwrap().push(event)
+}
+
+#[constructor(10)]
+extern "C" fn ctor_10() {
+    record("ctor_10");
+}
+
+#[constructor(0)]
+extern "C" fn ctor_0() {
+    record("ctor_0");
+}
+
+#[dynamic(lazy, link_section = "static_init_ext_ram")]
+static BUF: [u8; 64] = {
+    record("lazy");
+    [1; 64]
+};
+
+extern "C" {
+    static __start_static_init_ext_ram: u8;
+    static __stop_static_init_ext_ram: u8;
+}
+
+fn in_section(p: *const u8) -> bool {
+    unsafe {
+        let start: *const u8 = &__start_static_init_ext_ram;
+        let stop: *const u8 = &__stop_static_init_ext_ram;
+        start <= p && p < stop
+    }
+}
+
+#[test]
+fn value_is_in_the_section() {
+    assert_eq!(BUF[3], 1);
+    assert!(in_section(SplitLazy::as_mut_ptr(&BUF) as *const u8));
+    assert!(!in_section(&BUF as *const SplitLazy<[u8; 64]> as *const u8));
+}
+
+// lesser lazy statics are initialized after the constructors with priority higher than 1
+#[test]
+fn initialized_between_constructors() {
+    assert_eq!(*EVENTS.lock().unwrap(), ["ctor_10", "lazy", "ctor_0"]);
+}
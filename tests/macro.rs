@@ -5,6 +5,7 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 #![cfg_attr(feature = "test_thread_local", feature(thread_local))]
+#![allow(static_mut_refs)]
 
 extern crate static_init;
 use static_init::{constructor, destructor, dynamic};
@@ -118,6 +119,131 @@ fn inner_static() {
     };
 }
 
+static mut BLOCK_INI: i32 = 0;
+
+static_init::constructor_block! { 10 =>
+    unsafe {
+        assert_eq!(BLOCK_INI, 0);
+        BLOCK_INI += 1;
+    }
+}
+
+static_init::constructor_block! {
+    unsafe {
+        assert_eq!(BLOCK_INI, 1);
+        BLOCK_INI += 1;
+    }
+}
+
+#[test]
+fn constructor_block() {
+    unsafe { assert_eq!(BLOCK_INI, 2) }
+}
+
+static mut SYMBOLIC: i32 = 0;
+
+#[constructor(first)]
+extern "C" fn symbolic_first() {
+    unsafe {
+        assert_eq!(SYMBOLIC, 0);
+        SYMBOLIC = 1;
+    }
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    assert!(std::env::args().next().is_some());
+}
+
+#[constructor(5)]
+extern "C" fn symbolic_middle() {
+    unsafe {
+        assert_eq!(SYMBOLIC, 1);
+        SYMBOLIC = 2;
+    }
+}
+
+#[constructor(last)]
+extern "C" fn symbolic_last() {
+    unsafe {
+        assert_eq!(SYMBOLIC, 2);
+        SYMBOLIC = 3;
+    }
+}
+
+#[destructor(last)]
+extern "C" fn symbolic_last_destructor() {}
+
+#[test]
+fn symbolic_priorities() {
+    unsafe { assert_eq!(SYMBOLIC, 3) }
+}
+
+#[allow(non_upper_case_globals)]
+#[test]
+fn raw_identifiers() {
+    static mut R: i32 = 0;
+
+    #[constructor]
+    extern "C" fn r#match() {
+        unsafe { R = 1 }
+    }
+
+    #[dynamic(0)]
+    static r#type: i32 = 2;
+
+    unsafe {
+        assert_eq!(R, 1);
+        assert_eq!(*r#type, 2);
+    }
+}
+
+macro_rules! emit_dynamic {
+    ($vis:vis $name:ident, $v:expr) => {
+        #[dynamic(0)]
+        $vis static $name: i32 = $v;
+
+        #[constructor]
+        extern "C" fn emitted() {}
+    };
+}
+
+mod emitted_1 {
+    use static_init::{constructor, dynamic};
+    emit_dynamic!(pub(crate) V, 1);
+}
+
+mod emitted_2 {
+    use static_init::{constructor, dynamic};
+    emit_dynamic!(pub(crate) V, 2);
+}
+
+#[test]
+fn macro_emitted_statics() {
+    unsafe {
+        assert_eq!(*emitted_1::V, 1);
+        assert_eq!(*emitted_2::V, 2);
+    }
+}
+
+#[test]
+fn static_info() {
+    use static_init::{ConstStatic, DropMode, InitMode, StaticFlavor};
+
+    #[dynamic(0)]
+    static I: u64 = 3;
+
+    let info = ConstStatic::info(unsafe { &I });
+    if cfg!(debug_mode) {
+        let info = info.unwrap();
+        assert_eq!(info.variable_name, "I");
+        assert_eq!(info.type_name, "u64");
+        assert_eq!(info.size, 8);
+        assert_eq!(info.flavor, StaticFlavor::ConstStatic);
+        assert_eq!(info.init_mode, InitMode::Dynamic(0));
+        assert_eq!(info.drop_mode, DropMode::None);
+    } else {
+        assert!(info.is_none());
+    }
+}
+
 #[dynamic(0)]
 static mut V0: A = A::new(unsafe { V1.0 } - 5);
 
@@ -154,6 +280,35 @@ fn dynamic_init() {
     }
 }
 
+#[repr(C)]
+struct Callbacks {
+    add:  extern "C" fn(i32, i32) -> i32,
+    base: i32,
+}
+
+extern "C" fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[dynamic(0)]
+static CALLBACKS: Callbacks = Callbacks {
+    add,
+    base: "40".parse().unwrap(),
+};
+
+struct CallbacksPtr(*const Callbacks);
+
+unsafe impl Sync for CallbacksPtr {}
+
+static CALLBACKS_PTR: CallbacksPtr =
+    CallbacksPtr(static_init::ConstStatic::as_ptr(unsafe { &CALLBACKS }));
+
+#[test]
+fn callback_table() {
+    let table = unsafe { &*CALLBACKS_PTR.0 };
+    assert_eq!((table.add)(table.base, 2), 42);
+}
+
 
 #[cfg(feature = "atexit")]
 mod atexit {
@@ -253,6 +408,116 @@ mod lazy {
         assert_eq!(DROP_COUNT.load(Ordering::Relaxed), 4);
     }
 
+    #[cfg(all(feature= "thread_local_drop",feature = "test_thread_local"))]
+    #[test]
+    fn thread_local_try_with() {
+        use core::sync::atomic::{AtomicBool, Ordering};
+        use static_init::{ThreadLocalConstLazy, ThreadLocalLazy};
+
+        #[thread_local]
+        #[dynamic(lazy, drop)]
+        static X: i32 = 10;
+
+        static X_DROPPED_SEEN: AtomicBool = AtomicBool::new(false);
+
+        struct C;
+
+        impl Drop for C {
+            fn drop(&mut self) {
+                let r = ThreadLocalConstLazy::try_with(unsafe { &*core::ptr::addr_of!(X) }, |x| *x);
+                X_DROPPED_SEEN.store(r.is_err(), Ordering::Relaxed);
+            }
+        }
+
+        #[thread_local]
+        #[dynamic(lazy, drop)]
+        static mut Y: C = C;
+
+        #[thread_local]
+        #[dynamic(lazy)]
+        static Z: i32 = 3;
+
+        assert_eq!(ThreadLocalLazy::try_with(&Z, |z| *z + 1), Ok(4));
+
+        std::thread::spawn(|| unsafe {
+            // X is registered first so it is dropped before Y.
+            assert_eq!(ThreadLocalConstLazy::try_with(&*core::ptr::addr_of!(X), |x| *x), Ok(10));
+            let _ = &*Y;
+        })
+        .join()
+        .unwrap();
+        assert!(X_DROPPED_SEEN.load(Ordering::Relaxed));
+    }
+
+    #[cfg(all(feature= "thread_local_drop",feature = "test_thread_local"))]
+    #[test]
+    fn thread_local_late_register() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        use static_init::{defer_at_thread_exit, ThreadLocalConstLazy};
+
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        static SEEN: AtomicUsize = AtomicUsize::new(0);
+
+        struct D(usize);
+
+        impl Drop for D {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        #[thread_local]
+        #[dynamic(lazy, drop)]
+        static LEAKED: D = D(1);
+
+        #[thread_local]
+        #[dynamic(lazy, drop, on_late_register = "error")]
+        static REFUSED: D = D(2);
+
+        std::thread::spawn(|| {
+            // run while the thread local destructors of the thread are running
+            defer_at_thread_exit! {
+                let leaked = ThreadLocalConstLazy::try_with(unsafe { &*core::ptr::addr_of!(LEAKED) }, |d| d.0);
+                let refused = ThreadLocalConstLazy::try_with(unsafe { &*core::ptr::addr_of!(REFUSED) }, |d| d.0);
+                if leaked == Ok(1) && refused.is_err() {
+                    SEEN.store(1, Ordering::Relaxed);
+                }
+            };
+        })
+        .join()
+        .unwrap();
+        assert_eq!(SEEN.load(Ordering::Relaxed), 1);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+    }
+
+    #[cfg(feature = "test_thread_local")]
+    #[test]
+    fn thread_local_override() {
+        use static_init::ThreadLocalLazy;
+
+        #[thread_local]
+        #[dynamic(lazy)]
+        static CTX: i32 = 1;
+
+        fn read() -> i32 {
+            *CTX
+        }
+
+        let r = unsafe {
+            ThreadLocalLazy::with_override(&CTX, 2, || {
+                read() + ThreadLocalLazy::with_override(&CTX, 3, read)
+            })
+        };
+        assert_eq!(r, 5);
+        assert_eq!(read(), 1);
+
+        let p = std::panic::catch_unwind(|| unsafe {
+            ThreadLocalLazy::with_override(&CTX, 4, || panic!("in override"))
+        });
+        assert!(p.is_err());
+        assert_eq!(read(), 1);
+    }
+
     use super::A;
     use static_init::dynamic;
     #[dynamic(lazy)]
@@ -269,9 +534,222 @@ mod lazy {
     #[dynamic(lazy, drop)]
     static mut L2: A = A::new(unsafe { L3.0 });
 
+    mod prio_drop {
+        use static_init::{destructor, dynamic};
+
+        static mut DROP_V: i32 = 0;
+
+        struct D(i32);
+
+        impl Drop for D {
+            fn drop(&mut self) {
+                unsafe {
+                    assert_eq!(self.0, DROP_V);
+                    DROP_V += 1;
+                };
+            }
+        }
+
+        #[dynamic(lazy, drop = 10)]
+        static D1: D = D(1);
+
+        #[dynamic(lazy, drop = 5)]
+        static mut D0: D = D(unsafe { D1.0 } - 1);
+
+        #[destructor(7)]
+        extern "C" fn check_d0_dropped() {
+            unsafe { assert_eq!(DROP_V, 1) }
+        }
+
+        #[destructor(20)]
+        extern "C" fn check_d1_dropped() {
+            unsafe { assert_eq!(DROP_V, 2) }
+        }
+    }
+
     #[test]
     fn lazy_init() {
         unsafe { assert_eq!(L0.0, 10) };
         assert_eq!(L1.0, 11);
     }
+
+    #[test]
+    fn get_unchecked() {
+        use static_init::Lazy;
+
+        #[dynamic(lazy)]
+        static G: Vec<i32> = vec![1, 2];
+
+        assert_eq!(G.len(), 2);
+        assert_eq!(unsafe { Lazy::get_unchecked(&G) }, &[1, 2]);
+    }
+
+    #[test]
+    fn init_backtrace() {
+        use static_init::Lazy;
+
+        #[dynamic(lazy)]
+        static BT: i32 = 1;
+
+        assert_eq!(*BT, 1);
+        assert_eq!(Lazy::init_backtrace(&BT).is_some(), cfg!(debug_mode));
+    }
+
+    #[test]
+    fn from_value() {
+        use static_init::{AnyLazyStatic, ConstLazy, Lazy};
+
+        #[cfg(debug_mode)]
+        macro_rules! info {
+            ($name:literal, $typ:ty, $flavor:ident) => {
+                static_init::StaticInfo {
+                    variable_name: $name,
+                    file_name:     file!(),
+                    line:          line!(),
+                    column:        column!(),
+                    type_name:     stringify!($typ),
+                    size:          core::mem::size_of::<$typ>(),
+                    flavor:        static_init::StaticFlavor::$flavor,
+                    init_mode:     static_init::InitMode::Const,
+                    drop_mode:     static_init::DropMode::None,
+                }
+            };
+        }
+
+        #[cfg(debug_mode)]
+        static V: Lazy<Vec<i32>> = Lazy::from_value(Vec::new(), info!("V", Vec<i32>, Lazy));
+        #[cfg(debug_mode)]
+        static C: ConstLazy<u32> = ConstLazy::from_value(7, info!("C", u32, ConstLazy));
+
+        #[cfg(not(debug_mode))]
+        static V: Lazy<Vec<i32>> = Lazy::from_value(Vec::new());
+        #[cfg(not(debug_mode))]
+        static C: ConstLazy<u32> = ConstLazy::from_value(7);
+
+        // before any dereference
+        assert!(Lazy::is_initialized(&V));
+        assert!(ConstLazy::is_initialized(&C));
+        assert!(AnyLazyStatic::is_initialized(&V));
+
+        assert!(V.is_empty());
+        assert_eq!(*C, 7);
+        if cfg!(debug_mode) {
+            assert_eq!(Lazy::info(&V).unwrap().variable_name, "V");
+            assert_eq!(ConstLazy::info(&C).unwrap().variable_name, "C");
+        }
+    }
+
+    #[cfg(feature = "test_override")]
+    #[test]
+    fn scoped_override() {
+        use static_init::{ConstLazy, Lazy};
+
+        #[dynamic(lazy)]
+        static CONFIG: String = "prod".to_string();
+
+        #[dynamic(lazy, drop)]
+        static LEVEL: u32 = 1;
+
+        {
+            let _g = unsafe { Lazy::scoped_override(&CONFIG, "test".to_string()) };
+            assert_eq!(*CONFIG, "test");
+            {
+                let _g = unsafe { Lazy::scoped_override(&CONFIG, "inner".to_string()) };
+                assert_eq!(*CONFIG, "inner");
+            }
+            assert_eq!(*CONFIG, "test");
+        }
+        assert_eq!(*CONFIG, "prod");
+
+        {
+            let _g = unsafe { ConstLazy::scoped_override(&LEVEL, 2) };
+            assert_eq!(unsafe { *LEVEL }, 2);
+        }
+        assert_eq!(unsafe { *LEVEL }, 1);
+    }
+
+    #[test]
+    fn prewarm() {
+        #[dynamic(lazy)]
+        static P0: Vec<i32> = vec![1, 2];
+
+        #[dynamic(lazy)]
+        static P1: usize = P0.len();
+
+        static_init::prewarm(&[&P1, &P0]);
+        assert_eq!(*P1, 2);
+        static_init::prewarm(&[]);
+    }
+
+    #[test]
+    fn fallible_initialization() {
+        use static_init::{ConstLazy, Lazy};
+
+        #[dynamic(lazy)]
+        static PARSED: Result<i32, std::num::ParseIntError> = "x1".parse();
+
+        #[dynamic(lazy, drop)]
+        static DROPPED: Result<i32, std::num::ParseIntError> = "12".parse();
+
+        let e = Lazy::try_get(&PARSED).unwrap_err();
+        assert!(core::ptr::eq(e, Lazy::try_get(&PARSED).unwrap_err()));
+        assert_eq!(ConstLazy::try_get(unsafe { &*core::ptr::addr_of!(DROPPED) }), Ok(&12));
+    }
+
+    #[test]
+    fn any_lazy_static() {
+        use static_init::AnyLazyStatic;
+
+        #[dynamic(lazy)]
+        static S: String = String::from("s");
+
+        #[dynamic(lazy)]
+        static N: u64 = 3;
+
+        static ALL: [&dyn AnyLazyStatic; 2] = [&S, &N];
+
+        for l in ALL.iter() {
+            l.force();
+            assert!(l.is_initialized());
+            assert_eq!(l.info().is_some(), cfg!(debug_mode));
+        }
+        assert_eq!(*S, "s");
+        assert_eq!(*N, 3);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn link_section() {
+        #[dynamic(lazy)]
+        #[link_section = ".data.static_init_test"]
+        static mut BUF: [u8; 64] = [1; 64];
+
+        unsafe {
+            BUF[3] = 4;
+            assert_eq!(BUF[2..4], [1, 4]);
+        }
+    }
+
+    #[dynamic(lazy, assert_init_before = "ORD_B")]
+    static ORD_A: i32 = 1;
+
+    #[dynamic(lazy)]
+    static ORD_B: i32 = *ORD_A + 1;
+
+    #[test]
+    fn assert_init_before() {
+        assert_eq!(*ORD_B, 2);
+    }
+
+    #[test]
+    fn order_assertions() {
+        use static_init::debug::{assert_initialized_before, assert_will_drop_after};
+
+        assert_eq!(L1.0, 11);
+        assert_initialized_before(unsafe { &L0 }, &L1);
+        assert_will_drop_after(&L1, unsafe { &L0 });
+
+        let r = std::panic::catch_unwind(|| assert_initialized_before(&L1, unsafe { &L0 }));
+        assert_eq!(r.is_err(), cfg!(debug_mode));
+    }
 }
@@ -0,0 +1,40 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg(feature = "lazy")]
+
+use static_init::Once;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[test]
+fn call_once() {
+    static O: Once = Once::new();
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    assert!(!O.is_completed());
+    let ths: Vec<_> = (0..4)
+        .map(|_| std::thread::spawn(|| O.call_once(|| {
+            COUNT.fetch_add(1, Ordering::Relaxed);
+        })))
+        .collect();
+    for th in ths {
+        th.join().unwrap();
+    }
+    assert!(O.is_completed());
+    assert_eq!(COUNT.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn poisoned() {
+    static O: Once = Once::new();
+
+    let r = std::panic::catch_unwind(|| O.call_once(|| panic!("init failure")));
+    assert!(r.is_err());
+    assert!(!O.is_completed());
+    O.call_once_force(|st| assert!(st.is_poisoned()));
+    assert!(O.is_completed());
+}
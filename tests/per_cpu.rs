@@ -0,0 +1,35 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+#![cfg(all(feature = "per_cpu", target_os = "linux"))]
+
+use static_init::PerCpuLazy;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static INITS: AtomicUsize = AtomicUsize::new(0);
+
+static COUNTERS: PerCpuLazy<AtomicUsize, 4> = PerCpuLazy::new(|| {
+    INITS.fetch_add(1, Ordering::Relaxed);
+    AtomicUsize::new(0)
+});
+
+#[test]
+fn one_value_per_slot() {
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                for _ in 0..100 {
+                    COUNTERS.get().fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    let total: usize = COUNTERS.iter().map(|c| c.load(Ordering::Relaxed)).sum();
+    assert_eq!(total, 800);
+    assert_eq!(COUNTERS.iter().count(), INITS.load(Ordering::Relaxed));
+    assert!(COUNTERS.slot(4).is_none());
+}
@@ -0,0 +1,19 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use static_init::sections::{self, prioritized_section, SectionName, CRT_XPTZ};
+
+const LAST: SectionName = prioritized_section(".init_array.", 0);
+
+#[test]
+fn section_names() {
+    assert_eq!(LAST.as_str(), ".init_array.65535");
+    assert_eq!(prioritized_section(CRT_XPTZ, 65535).as_str(), ".CRT$XPTZ00000");
+    assert_eq!(prioritized_section("", 65434).to_string(), "00101");
+    assert!(sections::init_section(0).is_some());
+    assert!(sections::fini_section(0).is_some());
+}
@@ -0,0 +1,37 @@
+// Copyright 2021 Olivier Kannengieser
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use core::mem::{align_of, size_of};
+use static_init::StaticStorage;
+
+#[test]
+fn layout() {
+    assert_eq!(size_of::<StaticStorage<u8>>(), size_of::<u8>());
+    assert_eq!(size_of::<StaticStorage<[u64; 3]>>(), size_of::<[u64; 3]>());
+    assert_eq!(align_of::<StaticStorage<u64>>(), align_of::<u64>());
+    assert_eq!(size_of::<StaticStorage<()>>(), 0);
+}
+
+#[test]
+fn init_through_pointer() {
+    static V: StaticStorage<[u32; 4]> = StaticStorage::uninit();
+    static SRC: [u32; 4] = [1, 2, 3, 4];
+
+    unsafe {
+        V.as_ptr().write(core::ptr::read_volatile(&SRC));
+        assert_eq!(*V.as_ptr(), [1, 2, 3, 4]);
+    }
+
+    let w = StaticStorage::new(String::from("w"));
+    unsafe {
+        assert_eq!(*w.as_ptr(), "w");
+        w.drop_in_place();
+        w.write(String::from("x"));
+        assert_eq!(*w.as_ptr(), "x");
+        w.drop_in_place();
+    }
+}